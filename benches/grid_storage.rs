@@ -0,0 +1,162 @@
+//! Heap use and sidewinder time of `Grid`'s per-cell direction bits, against the layout it
+//! replaced: every cell holding its neighbours' positions and a `HashSet` of linked positions.
+//!
+//! Run with `cargo bench --bench grid_storage`. Under `cargo test` it only builds a small maze
+//! of each kind, to check that the benchmark still works.
+
+use maze::grid::{Grid, Position};
+use maze::random;
+use maze::sidewinder::sidewinder;
+use rand::prelude::StdRng;
+use rand::{Rng, SeedableRng};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashSet;
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Counts live heap bytes, so that the benchmark can tell how much memory a grid holds on to.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// South and west aren't needed by sidewinder, but are kept so that a cell is as large as before.
+#[allow(dead_code)]
+struct HashSetCell {
+    north: Option<Position>,
+    south: Option<Position>,
+    east: Option<Position>,
+    west: Option<Position>,
+    links: HashSet<Position>,
+}
+
+/// The old layout, with just enough of its API to run sidewinder.
+struct HashSetGrid {
+    cells: Vec<Vec<HashSetCell>>,
+}
+
+impl HashSetGrid {
+    fn new(rows: usize, columns: usize) -> Self {
+        let (rows, columns) = (rows as isize, columns as isize);
+        let cells = (0..rows)
+            .map(|y| {
+                (0..columns)
+                    .map(|x| HashSetCell {
+                        north: if y > 0 { Some((x, y - 1)) } else { None },
+                        south: if y < rows - 1 { Some((x, y + 1)) } else { None },
+                        east: if x < columns - 1 {
+                            Some((x + 1, y))
+                        } else {
+                            None
+                        },
+                        west: if x > 0 { Some((x - 1, y)) } else { None },
+                        links: HashSet::new(),
+                    })
+                    .collect()
+            })
+            .collect();
+        HashSetGrid { cells }
+    }
+
+    fn cell(&self, (x, y): Position) -> &HashSetCell {
+        &self.cells[y as usize][x as usize]
+    }
+
+    fn link_cell(&mut self, from: Position, to: Position) {
+        self.cells[from.1 as usize][from.0 as usize]
+            .links
+            .insert(to);
+        self.cells[to.1 as usize][to.0 as usize].links.insert(from);
+    }
+}
+
+/// `sidewinder`, drawing the same random numbers.
+fn hash_set_sidewinder<T: Rng>(grid: &mut HashSetGrid, rng: &mut T) {
+    let mut run = vec![];
+    for y in 0..grid.cells.len() {
+        run.clear();
+        for x in 0..grid.cells[y].len() {
+            let pos = (x as isize, y as isize);
+            run.push(pos);
+            let east = grid.cell(pos).east;
+            let should_close_out =
+                east.is_none() || (grid.cell(pos).north.is_some() && random::coin(rng));
+            if should_close_out {
+                if let Some(member) = random::choose(rng, &run).cloned() {
+                    if let Some(north) = grid.cell(member).north {
+                        grid.link_cell(member, north);
+                    }
+                }
+                run.clear();
+            } else if let Some(east) = east {
+                grid.link_cell(pos, east);
+            }
+        }
+    }
+}
+
+/// Heap bytes held by the grid `new` returns, then after `generate` has carved it, and how long
+/// carving took.
+fn measure<G>(new: impl FnOnce() -> G, generate: impl FnOnce(&mut G)) -> (usize, usize, Duration) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let mut grid = new();
+    let empty = ALLOCATED.load(Ordering::Relaxed) - before;
+    let start = Instant::now();
+    generate(&mut grid);
+    let elapsed = start.elapsed();
+    let carved = ALLOCATED.load(Ordering::Relaxed) - before;
+    (empty, carved, elapsed)
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`; `cargo test` doesn't.
+    let sizes: &[usize] = if env::args().any(|arg| arg == "--bench") {
+        &[500, 1000, 2000]
+    } else {
+        &[10]
+    };
+    for &size in sizes {
+        let cells = (size * size) as f64;
+        let results = [
+            (
+                "direction bits",
+                measure(
+                    || Grid::new(size, size),
+                    |grid| sidewinder(grid, &mut StdRng::seed_from_u64(1)).unwrap(),
+                ),
+            ),
+            (
+                "hash sets",
+                measure(
+                    || HashSetGrid::new(size, size),
+                    |grid| hash_set_sidewinder(grid, &mut StdRng::seed_from_u64(1)),
+                ),
+            ),
+        ];
+        for (name, (empty, carved, elapsed)) in results.iter() {
+            println!(
+                "{0}x{0} {1}: {2:.1} bytes per cell empty, {3:.1} carved, sidewinder in {4:?}",
+                size,
+                name,
+                *empty as f64 / cells,
+                *carved as f64 / cells,
+                elapsed
+            );
+        }
+    }
+}
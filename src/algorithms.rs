@@ -0,0 +1,186 @@
+use crate::binary_tree::{binary_tree, binary_tree_traced};
+use crate::grid::{Grid, GridError, Position};
+use crate::recursive_backtracker::{recursive_backtracker, recursive_backtracker_traced};
+use crate::sidewinder::{sidewinder, sidewinder_traced};
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+/// The generators that work on a plain `Grid`. The ids are part of `MazeCode`s and must never
+/// be reused.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Algorithm {
+    BinaryTree,
+    Sidewinder,
+    RecursiveBacktracker,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 3] = [
+        Algorithm::BinaryTree,
+        Algorithm::Sidewinder,
+        Algorithm::RecursiveBacktracker,
+    ];
+
+    pub fn id(self) -> u8 {
+        match self {
+            Algorithm::BinaryTree => 0,
+            Algorithm::Sidewinder => 1,
+            Algorithm::RecursiveBacktracker => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Algorithm> {
+        Algorithm::ALL.iter().copied().find(|a| a.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::BinaryTree => "binary_tree",
+            Algorithm::Sidewinder => "sidewinder",
+            Algorithm::RecursiveBacktracker => "recursive_backtracker",
+        }
+    }
+
+    pub fn generate<T: Rng>(self, grid: &mut Grid, rng: &mut T) -> Result<(), GridError> {
+        match self {
+            Algorithm::BinaryTree => binary_tree(grid, rng),
+            Algorithm::Sidewinder => sidewinder(grid, rng),
+            Algorithm::RecursiveBacktracker => recursive_backtracker(grid, rng),
+        }
+    }
+
+    /// Generates like `generate`, reporting every step to `trace` as it happens. Consumes the
+    /// same random numbers, so the resulting maze is identical.
+    pub fn generate_traced<T: Rng>(
+        self,
+        grid: &mut Grid,
+        rng: &mut T,
+        trace: impl FnMut(Step),
+    ) -> Result<(), GridError> {
+        match self {
+            Algorithm::BinaryTree => binary_tree_traced(grid, rng, trace),
+            Algorithm::Sidewinder => sidewinder_traced(grid, rng, trace),
+            Algorithm::RecursiveBacktracker => recursive_backtracker_traced(grid, rng, trace),
+        }
+    }
+
+    /// Generates a maze into `grid` and returns the steps that carved it, so that they can be
+    /// replayed one at a time onto an empty grid of the same size.
+    pub fn steps<T: Rng>(self, grid: &mut Grid, rng: &mut T) -> Result<Vec<Step>, GridError> {
+        let mut steps = vec![];
+        self.generate_traced(grid, rng, |step| steps.push(step))?;
+        Ok(steps)
+    }
+}
+
+/// One step of a generator at work.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Step {
+    /// The generator moved on to this cell.
+    Visit(Position),
+    /// Sidewinder's current run, ending with the cell just added. The run closes out by carving
+    /// north from one of its cells.
+    Run(Vec<Position>),
+    /// A passage was carved between two adjacent cells.
+    Link(Position, Position),
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL
+            .iter()
+            .copied()
+            .find(|a| a.name() == s)
+            .ok_or_else(|| {
+                let names = Algorithm::ALL.iter().map(|a| a.name()).collect::<Vec<_>>();
+                anyhow::anyhow!(
+                    "unknown algorithm `{}`, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::{Algorithm, Step};
+    use crate::grid::Grid;
+    use crate::test_utils::check;
+    use expect_test::expect;
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_algorithm_ids() {
+        for algorithm in Algorithm::ALL.iter() {
+            assert_eq!(Algorithm::from_id(algorithm.id()), Some(*algorithm));
+            assert_eq!(algorithm.name().parse::<Algorithm>().unwrap(), *algorithm);
+        }
+        assert_eq!(
+            "prim".parse::<Algorithm>().unwrap_err().to_string(),
+            "unknown algorithm `prim`, expected one of binary_tree, sidewinder, recursive_backtracker"
+        );
+    }
+
+    #[test]
+    fn test_steps_replay_to_the_same_maze() {
+        for algorithm in Algorithm::ALL.iter() {
+            let mut expected = Grid::new(6, 7);
+            algorithm
+                .generate(&mut expected, &mut StdRng::seed_from_u64(5))
+                .unwrap();
+
+            let mut traced = Grid::new(6, 7);
+            let steps = algorithm
+                .steps(&mut traced, &mut StdRng::seed_from_u64(5))
+                .unwrap();
+            assert_eq!(traced, expected);
+
+            let mut replayed = Grid::new(6, 7);
+            for step in steps {
+                if let Step::Link(from, to) = step {
+                    replayed.link_cell(from, to, true);
+                }
+            }
+            assert_eq!(replayed, expected);
+        }
+    }
+
+    #[test]
+    fn test_sidewinder_steps() {
+        let mut grid = Grid::new(2, 3);
+        let steps = Algorithm::Sidewinder
+            .steps(&mut grid, &mut StdRng::seed_from_u64(2))
+            .unwrap();
+        let steps = steps
+            .iter()
+            .map(|step| match step {
+                Step::Visit(pos) => format!("visit {:?}", pos),
+                Step::Run(run) => format!("run {:?}", run),
+                Step::Link(from, to) => format!("link {:?} {:?}", from, to),
+            })
+            .collect::<Vec<_>>();
+        check(&steps.join("\n"), expect![[r#"
+            run [(0, 0)]
+            link (0, 0) (1, 0)
+            run [(0, 0), (1, 0)]
+            link (1, 0) (2, 0)
+            run [(0, 0), (1, 0), (2, 0)]
+            run [(0, 1)]
+            link (0, 1) (1, 1)
+            run [(0, 1), (1, 1)]
+            link (0, 1) (0, 0)
+            run [(2, 1)]
+            link (2, 1) (2, 0)"#]]);
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{bail, Context};
+use maze::algorithms::Algorithm;
+use maze::maze_code::MazeCode;
+use maze::raster::RenderOptions;
+use maze::svg::SvgLayers;
+use rand::{thread_rng, Rng};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(name = "maze-cli", about = "Generates mazes without opening the game.")]
+enum Command {
+    /// Generates a maze and writes it out in the chosen format.
+    Generate(Generate),
+}
+
+#[derive(StructOpt)]
+struct Generate {
+    /// binary_tree, sidewinder or recursive_backtracker
+    #[structopt(long, default_value = "sidewinder")]
+    algo: Algorithm,
+    #[structopt(long, default_value = "10")]
+    rows: usize,
+    #[structopt(long, default_value = "10")]
+    cols: usize,
+    /// Picked at random if left out. The maze code printed on stderr reproduces the maze
+    /// either way.
+    #[structopt(long)]
+    seed: Option<u64>,
+    /// ascii, png, svg or json
+    #[structopt(long, default_value = "ascii")]
+    format: Format,
+    /// Where to write the maze; stdout if left out. Required for png.
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+}
+
+enum Format {
+    Ascii,
+    Png,
+    Svg,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(Format::Ascii),
+            "png" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
+            "json" => Ok(Format::Json),
+            _ => bail!(
+                "unknown format `{}`, expected one of ascii, png, svg, json",
+                s
+            ),
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    match Command::from_args() {
+        Command::Generate(args) => generate(args),
+    }
+}
+
+fn generate(args: Generate) -> anyhow::Result<()> {
+    if args.rows == 0 || args.cols == 0 {
+        bail!("a maze needs at least one row and one column");
+    }
+    if let (Format::Png, None) = (&args.format, &args.output) {
+        bail!("png output needs a file, pass -o <path>");
+    }
+    let code = MazeCode {
+        algorithm: args.algo,
+        rows: args.rows,
+        columns: args.cols,
+        seed: args.seed.unwrap_or_else(|| thread_rng().gen()),
+    };
+    let grid = code.generate()?;
+    eprintln!("maze code: {}", code);
+
+    let text = match args.format {
+        Format::Ascii => grid.to_string(),
+        Format::Svg => grid.to_svg(&RenderOptions::default(), &SvgLayers::default()),
+        Format::Json => serde_json::to_string_pretty(&grid)? + "\n",
+        Format::Png => {
+            let path = args.output.unwrap();
+            return grid.to_png(path, RenderOptions::default());
+        }
+    };
+    match args.output {
+        Some(path) => {
+            fs::write(&path, text).with_context(|| format!("can't write {}", path.display()))
+        }
+        None => Ok(io::stdout().write_all(text.as_bytes())?),
+    }
+}
@@ -0,0 +1,179 @@
+use crate::player::{Player, PlayerMovedEvent};
+use crate::{MazeReadyEvent, Tile, TileScale, SPRITE_SIZE};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::render::camera::Camera;
+
+const MAX_ZOOM: f32 = 16.;
+/// How much one notch of the mouse wheel zooms in or out.
+const ZOOM_STEP: f32 = 1.25;
+/// Roughly how many pixels a touchpad scrolls per wheel notch.
+const PIXELS_PER_LINE: f32 = 40.;
+/// How quickly the camera catches up with the player, as a fraction of the distance per second.
+const FOLLOW_RATE: f32 = 8.;
+
+/// Marks the camera drawing the maze, as opposed to the HUD.
+pub struct MainCamera;
+
+/// What part of the board the main camera shows. Zoom 1 fits the whole maze in the window,
+/// which is where `position_translation` lays it out.
+pub struct View {
+    zoom: f32,
+    center: Vec2,
+    /// Whether the camera keeps the player in the middle; dragging stops it until they move.
+    follow: bool,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            zoom: 1.,
+            center: Vec2::zero(),
+            follow: true,
+        }
+    }
+}
+
+impl View {
+    /// Zooms by `notches` of the mouse wheel, positive to zoom in.
+    fn zoom_by(&mut self, notches: f32) {
+        self.zoom = (self.zoom * ZOOM_STEP.powf(notches)).clamp(1., MAX_ZOOM);
+    }
+
+    /// The rectangle of the board in view in a window of `size`, as its corners.
+    fn area(&self, size: Vec2) -> (Vec2, Vec2) {
+        let half = size / (2. * self.zoom);
+        (self.center - half, self.center + half)
+    }
+}
+
+/// Whether a square of `half_extent` around `pos` overlaps `area`.
+fn overlaps((min, max): (Vec2, Vec2), pos: Vec2, half_extent: f32) -> bool {
+    pos.x + half_extent >= min.x
+        && pos.x - half_extent <= max.x
+        && pos.y + half_extent >= min.y
+        && pos.y - half_extent <= max.y
+}
+
+/// Zooms back out to show the whole maze.
+pub struct FitAllEvent;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(View::default())
+            .add_event::<FitAllEvent>()
+            .add_system(zoom_and_pan.system())
+            .add_system_to_stage(stage::POST_UPDATE, camera_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, cull_system.system());
+    }
+}
+
+/// Zooms with the mouse wheel and pans while the left button is held down.
+fn zoom_and_pan(
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut wheel_reader: Local<EventReader<MouseWheel>>,
+    mut cursor_reader: Local<EventReader<CursorMoved>>,
+    mut fit_reader: Local<EventReader<FitAllEvent>>,
+    mut moved_reader: Local<EventReader<PlayerMovedEvent>>,
+    mut ready_reader: Local<EventReader<MazeReadyEvent>>,
+    mut last_cursor: Local<Option<Vec2>>,
+    wheel_events: Res<Events<MouseWheel>>,
+    cursor_events: Res<Events<CursorMoved>>,
+    fit_events: Res<Events<FitAllEvent>>,
+    moved_events: Res<Events<PlayerMovedEvent>>,
+    ready_events: Res<Events<MazeReadyEvent>>,
+    mut view: ResMut<View>,
+) {
+    for event in wheel_reader.iter(&wheel_events) {
+        let notches = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        };
+        view.zoom_by(notches);
+    }
+    for event in cursor_reader.iter(&cursor_events) {
+        if let Some(last) = *last_cursor {
+            if mouse_buttons.pressed(MouseButton::Left) && event.position != last {
+                let delta = (event.position - last) / view.zoom;
+                view.center -= delta;
+                view.follow = false;
+            }
+        }
+        *last_cursor = Some(event.position);
+    }
+    if moved_reader.iter(&moved_events).next().is_some()
+        || ready_reader.iter(&ready_events).next().is_some()
+    {
+        view.follow = true;
+    }
+    if fit_reader.iter(&fit_events).next().is_some() {
+        *view = View::default();
+    }
+}
+
+/// Eases the camera towards the player while following, and applies the zoom.
+fn camera_system(
+    time: Res<Time>,
+    mut view: ResMut<View>,
+    players: Query<&Transform, With<Player>>,
+    mut cameras: Query<&mut Transform, (With<Camera>, With<MainCamera>)>,
+) {
+    if view.zoom <= 1. {
+        // The whole maze is in the window anyway.
+        view.center = Vec2::zero();
+    } else if view.follow {
+        if let Some(player) = players.iter().next() {
+            let target = player.translation.truncate();
+            let t = (FOLLOW_RATE * time.delta_seconds()).min(1.);
+            view.center = view.center + (target - view.center) * t;
+        }
+    }
+    for mut transform in cameras.iter_mut() {
+        transform.translation = view.center.extend(transform.translation.z);
+        transform.scale = Vec3::new(1. / view.zoom, 1. / view.zoom, 1.);
+    }
+}
+
+/// Hides the cells outside the camera's view so that they aren't drawn.
+fn cull_system(
+    windows: Res<Windows>,
+    view: Res<View>,
+    mut tiles: Query<(&Transform, Option<&TileScale>, &mut Visible), With<Tile>>,
+) {
+    let window = windows.get_primary().unwrap();
+    let area = view.area(Vec2::new(window.width(), window.height()));
+    for (transform, scale, mut visible) in tiles.iter_mut() {
+        // Half a cell, whatever the size of the sprite's texture.
+        let half_extent = transform.scale.x * SPRITE_SIZE / scale.map_or(1., |s| s.0) / 2.;
+        let is_visible = overlaps(area, transform.translation.truncate(), half_extent);
+        if visible.is_visible != is_visible {
+            visible.is_visible = is_visible;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{overlaps, View, MAX_ZOOM};
+    use bevy::prelude::Vec2;
+
+    #[test]
+    fn test_view() {
+        let mut view = View::default();
+        view.zoom_by(-3.);
+        assert_eq!(view.zoom, 1.);
+        view.zoom_by(100.);
+        assert_eq!(view.zoom, MAX_ZOOM);
+
+        view.zoom = 4.;
+        view.center = Vec2::new(100., -40.);
+        let area = view.area(Vec2::new(400., 200.));
+        assert_eq!(area, (Vec2::new(50., -65.), Vec2::new(150., -15.)));
+        assert!(overlaps(area, Vec2::new(100., -40.), 1.));
+        assert!(overlaps(area, Vec2::new(155., -40.), 10.));
+        assert!(!overlaps(area, Vec2::new(155., -40.), 1.));
+        assert!(!overlaps(area, Vec2::new(100., 0.), 10.));
+    }
+}
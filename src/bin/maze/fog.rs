@@ -0,0 +1,150 @@
+use crate::player::Player;
+use crate::{Animation, MazeReadyEvent, Position};
+use bevy::prelude::*;
+use maze::grid::Grid;
+use std::collections::HashSet;
+
+/// Covers cells the player hasn't seen yet.
+const HIDDEN_COLOR: Color = Color::rgb_linear(0.05, 0.05, 0.08);
+/// Dims cells the player has seen before but can't see from where they stand.
+const EXPLORED_COLOR: Color = Color::rgba_linear(0.05, 0.05, 0.08, 0.6);
+
+/// Marks the sprite drawn over a cell's walls to hide or dim it.
+pub struct Shade;
+
+/// The cells the player can see now and has seen since the level started.
+#[derive(Default)]
+pub struct Fog {
+    pub enabled: bool,
+    visible: HashSet<(isize, isize)>,
+    explored: HashSet<(isize, isize)>,
+}
+
+impl Fog {
+    pub fn new(enabled: bool) -> Self {
+        Fog {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Forgets everything seen, for a new maze.
+    fn clear(&mut self) {
+        self.visible.clear();
+        self.explored.clear();
+    }
+
+    /// Looks down every corridor leaving `pos`.
+    pub fn look(&mut self, grid: &Grid, pos: (isize, isize)) {
+        self.visible = grid.visible_from(pos).into_iter().collect();
+        self.explored.extend(self.visible.iter().copied());
+    }
+
+    /// Whether the player has seen `pos` since the level started, even with the fog lifted.
+    pub fn is_explored(&self, pos: (isize, isize)) -> bool {
+        self.explored.contains(&pos)
+    }
+
+    /// The colour of the shade over `pos`.
+    fn shade(&self, pos: (isize, isize)) -> Color {
+        if !self.enabled || self.visible.contains(&pos) {
+            Color::NONE
+        } else if self.explored.contains(&pos) {
+            EXPLORED_COLOR
+        } else {
+            HIDDEN_COLOR
+        }
+    }
+}
+
+pub struct ToggleFogEvent;
+
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<ToggleFogEvent>()
+            .add_system_to_stage(stage::POST_UPDATE, fog_system.system());
+    }
+}
+
+/// Updates what the player can see whenever they move, and redraws the shades if that changed.
+/// The fog lifts while the generator is being animated.
+fn fog_system(
+    mut ready_reader: Local<EventReader<MazeReadyEvent>>,
+    mut toggle_reader: Local<EventReader<ToggleFogEvent>>,
+    mut was_lifted: Local<bool>,
+    ready_events: Res<Events<MazeReadyEvent>>,
+    toggle_events: Res<Events<ToggleFogEvent>>,
+    grid: Res<Grid>,
+    animation: Res<Animation>,
+    mut fog: ResMut<Fog>,
+    players: Query<&Position, (With<Player>, Changed<Position>)>,
+    mut shades: Query<(&Position, &mut TextureAtlasSprite), With<Shade>>,
+) {
+    let ready = ready_reader.iter(&ready_events).next().is_some();
+    let toggled = toggle_reader.iter(&toggle_events).count() % 2 == 1;
+    if toggled {
+        fog.enabled = !fog.enabled;
+    }
+    if ready {
+        fog.clear();
+    }
+    let mut moved = false;
+    for pos in players.iter() {
+        fog.look(&grid, pos.cell());
+        moved = true;
+    }
+    let lifted = animation.is_running();
+    if !ready && !toggled && !moved && lifted == *was_lifted {
+        return;
+    }
+    *was_lifted = lifted;
+
+    for (pos, mut sprite) in shades.iter_mut() {
+        let color = if lifted {
+            Color::NONE
+        } else {
+            fog.shade(pos.cell())
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fog, EXPLORED_COLOR, HIDDEN_COLOR};
+    use bevy::prelude::Color;
+    use maze::grid::Grid;
+
+    #[test]
+    fn test_fog_shade() {
+        let grid = "\
++---+---+---+
+|           |
++---+---+   +
+|   |   |   |
++---+---+---+
+"
+        .parse::<Grid>()
+        .unwrap();
+
+        let mut fog = Fog::new(true);
+        fog.look(&grid, (0, 0));
+        assert_eq!(fog.shade((2, 0)), Color::NONE);
+        assert_eq!(fog.shade((2, 1)), HIDDEN_COLOR);
+
+        fog.look(&grid, (2, 1));
+        assert_eq!(fog.shade((0, 0)), EXPLORED_COLOR);
+        assert_eq!(fog.shade((2, 0)), Color::NONE);
+        assert_eq!(fog.shade((1, 1)), HIDDEN_COLOR);
+
+        fog.enabled = false;
+        assert_eq!(fog.shade((1, 1)), Color::NONE);
+        fog.clear();
+        fog.enabled = true;
+        assert_eq!(fog.shade((2, 1)), HIDDEN_COLOR);
+    }
+}
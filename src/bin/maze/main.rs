@@ -0,0 +1,803 @@
+mod camera;
+mod fog;
+mod minimap;
+mod player;
+mod score;
+mod tileset;
+mod walls;
+
+use crate::camera::{CameraPlugin, FitAllEvent, MainCamera};
+use crate::fog::{Fog, FogPlugin, Shade, ToggleFogEvent};
+use crate::minimap::{MinimapPlugin, ToggleMinimapEvent};
+use crate::player::{MoveSpeed, PlayerPlugin};
+use crate::score::{ScorePlugin, Scores};
+use crate::tileset::{load_tilesets, NextThemeEvent, Themes, TilesetPlugin, WallTile};
+use crate::walls::{WallStyle, WallsPlugin};
+use bevy::asset::FileAssetIo;
+use bevy::prelude::*;
+use bevy::render::texture::{Extent3d, TextureDimension, TextureFormat};
+use bevy::window::ReceivedCharacter;
+use maze::algorithms::{Algorithm, Step};
+use maze::distances::Gradient;
+use maze::grid::Grid;
+use maze::maze_code::MazeCode;
+use rand::{thread_rng, Rng};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+impl Position {
+    /// The position in the `Grid`'s coordinates.
+    fn cell(&self) -> (isize, isize) {
+        (self.x as isize, self.y as isize)
+    }
+}
+
+impl From<(isize, isize)> for Position {
+    fn from((x, y): (isize, isize)) -> Self {
+        Position {
+            x: x as i32,
+            y: y as i32,
+        }
+    }
+}
+
+/// Draws the entity at this fraction of a cell's size instead of filling the cell.
+struct TileScale(f32);
+
+/// Draws the entity this many cells away from its `Position`, for movement in between cells.
+#[derive(Default)]
+struct DrawOffset(Vec2);
+
+#[derive(Debug)]
+struct Size {
+    width: f32,
+    height: f32,
+}
+
+impl Size {
+    pub fn square(x: f32) -> Self {
+        Self {
+            width: x,
+            height: x,
+        }
+    }
+}
+
+const MAX_SIZE: usize = 100;
+const SCREEN_SIZE: f32 = 400.;
+const SPRITE_SIZE: f32 = 4.;
+/// Space kept free above the maze for each line of the HUD, and for all of them.
+const HUD_LINE_HEIGHT: f32 = 20.;
+const HUD_HEIGHT: f32 = 2. * HUD_LINE_HEIGHT + 4.;
+const FONT: &str = "fonts/DejaVuSansMono.ttf";
+
+#[derive(StructOpt)]
+#[structopt(name = "maze", about = "Walks through randomly generated mazes.")]
+struct Options {
+    #[structopt(long, default_value = "10", parse(try_from_str = parse_size))]
+    rows: usize,
+    #[structopt(long, default_value = "10", parse(try_from_str = parse_size))]
+    cols: usize,
+    /// A maze code to start with. Its size takes precedence over --rows and --cols.
+    #[structopt(parse(try_from_str = parse_maze_code))]
+    code: Option<MazeCode>,
+    /// Show the generator carving the maze step by step. Toggle with V.
+    #[structopt(long)]
+    animate: bool,
+    /// How fast `--animate` replays the generator. Change with , and .
+    #[structopt(long, default_value = "30")]
+    steps_per_second: f32,
+    /// How many cells per second the player slides.
+    #[structopt(long, default_value = "8")]
+    move_speed: f32,
+    /// Only show the cells the player has seen. Toggle with F.
+    #[structopt(long)]
+    fog: bool,
+    /// Where the best result for every maze code played is kept.
+    #[structopt(long, default_value = "maze-scores.json")]
+    scores: PathBuf,
+    /// How thick the walls are, as a fraction of a cell.
+    #[structopt(long, default_value = "0.25", parse(try_from_str = parse_thickness))]
+    wall_thickness: f32,
+    /// The colour of the walls as RRGGBB hex.
+    #[structopt(long, default_value = "000000", parse(try_from_str = parse_color))]
+    wall_color: Color,
+    /// How to draw the walls: "lines", or the name of a tileset. Cycle through them with T.
+    #[structopt(long, default_value = "lines")]
+    theme: String,
+    /// Where to look for tileset descriptors [default: the game's assets/tilesets].
+    #[structopt(long)]
+    tilesets: Option<PathBuf>,
+}
+
+struct Atlases {
+    floor: Handle<TextureAtlas>,
+}
+
+/// The number of rows and columns of cell sprites currently spawned.
+#[derive(Default)]
+struct Board {
+    rows: usize,
+    columns: usize,
+}
+
+/// Marks every sprite that belongs to a cell, so that they can be despawned on resize.
+struct Tile;
+
+/// Marks the text overlay describing the current maze.
+struct Hud;
+
+/// Marks the plain sprite beneath a cell's walls, tinted by the heat map.
+struct Floor;
+
+/// Colours the floor of every cell by its distance from `root`.
+#[derive(Default)]
+struct HeatMap {
+    enabled: bool,
+    root: (isize, isize),
+    gradient: Gradient,
+}
+
+/// Replays the generator's steps onto the `Grid` a few at a time, highlighting the cell it is
+/// working on and sidewinder's current run.
+struct Animation {
+    enabled: bool,
+    steps_per_second: f32,
+    steps: VecDeque<Step>,
+    /// Progress towards the next step, carried over between frames.
+    pending: f32,
+    current: Option<(isize, isize)>,
+    run: Vec<(isize, isize)>,
+}
+
+impl Animation {
+    fn is_running(&self) -> bool {
+        !self.steps.is_empty()
+    }
+}
+
+const CURRENT_COLOR: Color = Color::rgb_linear(0.9, 0.25, 0.1);
+const RUN_COLOR: Color = Color::rgb_linear(1., 0.7, 0.2);
+const MIN_STEPS_PER_SECOND: f32 = 1.;
+const MAX_STEPS_PER_SECOND: f32 = 1000.;
+
+/// A maze code being typed in after pressing Tab.
+#[derive(Default)]
+struct CodeEntry {
+    active: bool,
+    buffer: String,
+}
+
+struct RegenerateEvent;
+
+/// Sent once the maze in `Grid` is complete, after any animation.
+struct MazeReadyEvent;
+
+struct ToggleHeatMapEvent;
+
+fn random_seed() -> u64 {
+    u64::from(thread_rng().gen::<u32>())
+}
+
+fn parse_size(s: &str) -> Result<usize, String> {
+    let size = s.parse::<usize>().map_err(|e| e.to_string())?;
+    if size == 0 || size > MAX_SIZE {
+        return Err(format!("must be between 1 and {}", MAX_SIZE));
+    }
+    Ok(size)
+}
+
+fn parse_thickness(s: &str) -> Result<f32, String> {
+    let thickness = s.parse::<f32>().map_err(|e| e.to_string())?;
+    if !(thickness > 0. && thickness < 1.) {
+        return Err("must be between 0 and 1".to_string());
+    }
+    Ok(thickness)
+}
+
+/// Parses an `RRGGBB` colour, optionally prefixed with `#`.
+fn parse_color(s: &str) -> Result<Color, String> {
+    let hex = s.trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(|| format!("{} isn't an RRGGBB colour", s))
+    };
+    if hex.len() != 6 {
+        return Err(format!("{} isn't an RRGGBB colour", s));
+    }
+    Ok(Color::rgb_u8(channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Whether a maze has room for a goal apart from `player::START`. A level only ends when the
+/// player moves onto the goal, so a single cell maze could never be completed.
+fn has_goal(rows: usize, columns: usize) -> bool {
+    rows * columns > 1
+}
+
+/// Parses a maze code, rejecting sizes the board can't show or play.
+fn parse_maze_code(s: &str) -> Result<MazeCode, String> {
+    let code = s.parse::<MazeCode>().map_err(|e| e.to_string())?;
+    if code.rows == 0 || code.columns == 0 || code.rows > MAX_SIZE || code.columns > MAX_SIZE {
+        return Err(format!(
+            "maze code is for a {}x{} maze, at most {}x{} is supported",
+            code.rows, code.columns, MAX_SIZE, MAX_SIZE
+        ));
+    }
+    if !has_goal(code.rows, code.columns) {
+        return Err("maze code is for a 1x1 maze, which has no room for a goal".to_string());
+    }
+    Ok(code)
+}
+
+fn main() {
+    let options = Options::from_args();
+    let code = options.code.unwrap_or_else(|| MazeCode {
+        algorithm: Algorithm::Sidewinder,
+        rows: options.rows,
+        columns: options.cols,
+        seed: random_seed(),
+    });
+    if !has_goal(code.rows, code.columns) {
+        structopt::clap::Error::with_description(
+            "a 1x1 maze has no room for a goal",
+            structopt::clap::ErrorKind::InvalidValue,
+        )
+        .exit()
+    }
+    // Where Bevy's asset server looks for `assets`, rather than the current directory.
+    let tilesets_dir = options
+        .tilesets
+        .unwrap_or_else(|| FileAssetIo::get_root_path().join("assets/tilesets"));
+    let tilesets = load_tilesets(&tilesets_dir).unwrap_or_else(|e| {
+        eprintln!("can't load tilesets: {:#}", e);
+        vec![]
+    });
+    let themes = Themes::new(tilesets, &options.theme).unwrap_or_else(|e| {
+        structopt::clap::Error::with_description(&e, structopt::clap::ErrorKind::InvalidValue)
+            .exit()
+    });
+    let scores = Scores::load(&options.scores).unwrap_or_else(|e| {
+        eprintln!("can't load scores, they won't be saved: {:#}", e);
+        Scores::default()
+    });
+    let animation = Animation {
+        enabled: options.animate,
+        steps_per_second: options
+            .steps_per_second
+            .clamp(MIN_STEPS_PER_SECOND, MAX_STEPS_PER_SECOND),
+        steps: VecDeque::new(),
+        pending: 0.,
+        current: None,
+        run: vec![],
+    };
+
+    App::build()
+        .add_resource(WindowDescriptor {
+            title: "maze".to_string(),
+            width: SCREEN_SIZE as f32,
+            height: SCREEN_SIZE as f32,
+            ..Default::default()
+        })
+        .add_resource(ClearColor(Color::rgb(255., 255., 255.)))
+        .add_plugins(DefaultPlugins)
+        .add_plugin(PlayerPlugin)
+        .add_plugin(FogPlugin)
+        .add_plugin(ScorePlugin)
+        .add_plugin(CameraPlugin)
+        .add_plugin(MinimapPlugin)
+        .add_plugin(WallsPlugin)
+        .add_plugin(TilesetPlugin)
+        .add_startup_system(setup.system())
+        .add_system(make_maze.system())
+        .add_system(position_translation.system())
+        .add_system(keyboard_input_system.system())
+        .add_system(hud_system.system())
+        .add_system(animation_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, heat_map_system.system())
+        .add_resource(Grid::new(code.rows, code.columns))
+        .add_resource(Board::default())
+        .add_resource(HeatMap::default())
+        .add_resource(code)
+        .add_resource(CodeEntry::default())
+        .add_resource(animation)
+        .add_resource(MoveSpeed(options.move_speed.max(0.1)))
+        .add_resource(Fog::new(options.fog))
+        .add_resource(scores)
+        .add_resource(themes)
+        .add_resource(WallStyle {
+            thickness: options.wall_thickness,
+            color: options.wall_color,
+        })
+        .add_event::<RegenerateEvent>()
+        .add_event::<MazeReadyEvent>()
+        .add_event::<ToggleHeatMapEvent>()
+        .run();
+}
+
+fn setup(
+    commands: &mut Commands,
+    asset_server: Res<AssetServer>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut regenerate_events: ResMut<Events<RegenerateEvent>>,
+) {
+    commands.spawn(Camera2dBundle::default()).with(MainCamera);
+    commands.spawn(CameraUiBundle::default());
+    commands
+        .spawn(TextBundle {
+            text: Text {
+                value: String::new(),
+                font: asset_server.load(FONT),
+                style: TextStyle {
+                    font_size: 16.,
+                    color: Color::BLACK,
+                    ..Default::default()
+                },
+            },
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(4.),
+                    left: Val::Px(8.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with(Hud);
+    let floor_texture = textures.add(Texture::new_fill(
+        Extent3d::new(SPRITE_SIZE as u32, SPRITE_SIZE as u32, 1),
+        TextureDimension::D2,
+        &[255, 255, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+    let floor_atlas_handle = texture_atlases.add(TextureAtlas::from_grid(
+        floor_texture,
+        Vec2::splat(SPRITE_SIZE),
+        1,
+        1,
+    ));
+
+    commands.insert_resource(Atlases {
+        floor: floor_atlas_handle,
+    });
+
+    regenerate_events.send(RegenerateEvent);
+}
+
+/// Spawns a floor, a wall tile and a shade sprite for every cell of `grid`. The tiles are picked
+/// by `tileset::TilesetPlugin`, unless the walls are drawn by `walls::WallsPlugin`.
+fn spawn_tiles(commands: &mut Commands, atlases: &Atlases, grid: &Grid) {
+    for (x, y) in grid.iter() {
+        let position = Position {
+            x: x as i32,
+            y: y as i32,
+        };
+        commands
+            .spawn(SpriteSheetBundle {
+                texture_atlas: atlases.floor.clone(),
+                sprite: TextureAtlasSprite {
+                    color: Color::NONE,
+                    index: 0,
+                },
+                ..Default::default()
+            })
+            .with(position)
+            .with(Tile)
+            .with(Floor);
+        commands
+            .spawn(SpriteSheetBundle {
+                texture_atlas: atlases.floor.clone(),
+                sprite: TextureAtlasSprite {
+                    color: Color::NONE,
+                    index: 0,
+                },
+                transform: Transform::from_translation(Vec3::new(0., 0., 1.)),
+                ..Default::default()
+            })
+            .with(position)
+            .with(TileScale(1.))
+            .with(Tile)
+            .with(WallTile);
+        commands
+            .spawn(SpriteSheetBundle {
+                texture_atlas: atlases.floor.clone(),
+                sprite: TextureAtlasSprite {
+                    color: Color::NONE,
+                    index: 0,
+                },
+                transform: Transform::from_translation(Vec3::new(0., 0., 1.5)),
+                ..Default::default()
+            })
+            .with(position)
+            .with(Tile)
+            .with(Shade);
+    }
+}
+
+fn make_maze(
+    commands: &mut Commands,
+    tiles: Query<Entity, With<Tile>>,
+    mut regenerate_reader: Local<EventReader<RegenerateEvent>>,
+    mut grid: ResMut<Grid>,
+    mut board: ResMut<Board>,
+    mut windows: ResMut<Windows>,
+    mut animation: ResMut<Animation>,
+    mut ready_events: ResMut<Events<MazeReadyEvent>>,
+    atlases: Res<Atlases>,
+    code: Res<MazeCode>,
+    regenerate_events: Res<Events<RegenerateEvent>>,
+) {
+    if regenerate_reader.iter(&regenerate_events).next().is_none() {
+        return;
+    }
+    animation.steps.clear();
+    let generated = if animation.enabled {
+        // Generate up front, then start from an empty grid and let `animation_system` replay
+        // the steps.
+        let mut finished = Grid::new(code.rows, code.columns);
+        code.algorithm
+            .steps(&mut finished, &mut code.rng())
+            .map(|steps| {
+                animation.steps.extend(steps);
+                animation.pending = 0.;
+                Grid::new(code.rows, code.columns)
+            })
+    } else {
+        code.generate()
+    };
+    match generated {
+        Ok(generated) => *grid = generated,
+        Err(e) => {
+            eprintln!("can't generate maze {}: {}", *code, e);
+            return;
+        }
+    }
+    if !animation.is_running() {
+        ready_events.send(MazeReadyEvent);
+    }
+    windows
+        .get_primary_mut()
+        .unwrap()
+        .set_title(format!("maze — {}", *code));
+
+    if (board.rows, board.columns) != (grid.rows(), grid.columns()) {
+        for entity in tiles.iter() {
+            commands.despawn(entity);
+        }
+        spawn_tiles(commands, &atlases, &grid);
+        board.rows = grid.rows();
+        board.columns = grid.columns();
+    }
+}
+
+fn hud_text(code: &MazeCode, animation: &Animation, themes: &Themes) -> String {
+    let mut text = format!(
+        "{}  {}x{}  seed {}",
+        code.algorithm, code.rows, code.columns, code.seed
+    );
+    if animation.enabled {
+        text.push_str(&format!("  {} steps/s", animation.steps_per_second));
+    }
+    if themes.name() != tileset::LINES {
+        text.push_str(&format!("  {}", themes.name()));
+    }
+    text
+}
+
+fn hud_system(
+    code: Res<MazeCode>,
+    animation: Res<Animation>,
+    themes: Res<Themes>,
+    mut query: Query<&mut Text, With<Hud>>,
+) {
+    let value = hud_text(&code, &animation, &themes);
+    for mut text in query.iter_mut() {
+        if text.value != value {
+            text.value = value.clone();
+        }
+    }
+}
+
+fn animation_system(
+    time: Res<Time>,
+    mut animation: ResMut<Animation>,
+    mut grid: ResMut<Grid>,
+    mut ready_events: ResMut<Events<MazeReadyEvent>>,
+    mut floors: Query<(&Position, &mut TextureAtlasSprite), With<Floor>>,
+) {
+    if !animation.is_running() {
+        return;
+    }
+    animation.pending += time.delta_seconds() * animation.steps_per_second;
+    while animation.pending >= 1. {
+        animation.pending -= 1.;
+        match animation.steps.pop_front() {
+            Some(Step::Visit(pos)) => {
+                animation.current = Some(pos);
+                animation.run.clear();
+            }
+            Some(Step::Run(run)) => {
+                animation.current = run.last().copied();
+                animation.run = run;
+            }
+            Some(Step::Link(from, to)) => grid.link_cell(from, to, true),
+            None => break,
+        }
+    }
+    if !animation.is_running() {
+        animation.pending = 0.;
+        animation.current = None;
+        animation.run.clear();
+        ready_events.send(MazeReadyEvent);
+        // `heat_map_system` takes the floor over from here.
+        return;
+    }
+    for (pos, mut sprite) in floors.iter_mut() {
+        let pos = (pos.x as isize, pos.y as isize);
+        sprite.color = if animation.current == Some(pos) {
+            CURRENT_COLOR
+        } else if animation.run.contains(&pos) {
+            RUN_COLOR
+        } else {
+            Color::NONE
+        };
+    }
+}
+
+fn heat_map_system(
+    mut ready_reader: Local<EventReader<MazeReadyEvent>>,
+    mut toggle_reader: Local<EventReader<ToggleHeatMapEvent>>,
+    ready_events: Res<Events<MazeReadyEvent>>,
+    toggle_events: Res<Events<ToggleHeatMapEvent>>,
+    grid: Res<Grid>,
+    animation: Res<Animation>,
+    mut heat_map: ResMut<HeatMap>,
+    mut query: Query<(&Position, &mut TextureAtlasSprite), With<Floor>>,
+) {
+    let ready = ready_reader.iter(&ready_events).next().is_some();
+    let toggled = toggle_reader.iter(&toggle_events).count() % 2 == 1;
+    if toggled {
+        heat_map.enabled = !heat_map.enabled;
+    }
+    if (!ready && !toggled) || animation.is_running() {
+        return;
+    }
+
+    let distances = if heat_map.enabled {
+        Some(grid.distances(heat_map.root))
+    } else {
+        None
+    };
+    for (pos, mut sprite) in query.iter_mut() {
+        let color = distances
+            .as_ref()
+            .and_then(|d| d.color((pos.x as isize, pos.y as isize), &heat_map.gradient));
+        sprite.color = match color {
+            Some(c) => Color::rgba_u8(c[0], c[1], c[2], c[3]),
+            None => Color::NONE,
+        };
+    }
+}
+
+/// The centre of cell `(x, y)` in a board of `columns` by `rows` tiles centred on the origin,
+/// with y pointing up.
+fn convert((x, y): (f32, f32), tile_size: f32, (columns, rows): (f32, f32)) -> (f32, f32) {
+    (
+        (x - (columns - 1.) / 2.) * tile_size,
+        ((rows - 1.) / 2. - y) * tile_size,
+    )
+}
+
+fn position_translation(
+    windows: Res<Windows>,
+    grid: Res<Grid>,
+    mut q: Query<(
+        &Position,
+        &mut Transform,
+        Option<&TileScale>,
+        Option<&DrawOffset>,
+    )>,
+) {
+    let window = windows.get_primary().unwrap();
+    let (columns, rows) = (grid.columns() as f32, grid.rows() as f32);
+    let tile_size = (window.width() / columns).min((window.height() - HUD_HEIGHT) / rows);
+    for (pos, mut transform, scale, offset) in q.iter_mut() {
+        let offset = offset.map_or(Vec2::zero(), |o| o.0);
+        let (x, y) = convert(
+            (pos.x as f32 + offset.x, pos.y as f32 + offset.y),
+            tile_size,
+            (columns, rows),
+        );
+        transform.translation = Vec3::new(x, y - HUD_HEIGHT / 2., transform.translation.z);
+        let scale = scale.map_or(1., |s| s.0);
+        transform.scale = Vec3::splat(tile_size * scale / SPRITE_SIZE);
+    }
+}
+
+fn keyboard_input_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut char_reader: Local<EventReader<ReceivedCharacter>>,
+    char_events: Res<Events<ReceivedCharacter>>,
+    mut entry: ResMut<CodeEntry>,
+    mut code: ResMut<MazeCode>,
+    mut windows: ResMut<Windows>,
+    mut animation: ResMut<Animation>,
+    mut regenerate_events: ResMut<Events<RegenerateEvent>>,
+    mut heat_map_events: ResMut<Events<ToggleHeatMapEvent>>,
+    mut fog_events: ResMut<Events<ToggleFogEvent>>,
+    mut fit_events: ResMut<Events<FitAllEvent>>,
+    mut minimap_events: ResMut<Events<ToggleMinimapEvent>>,
+    mut theme_events: ResMut<Events<NextThemeEvent>>,
+) {
+    let typed = char_reader
+        .iter(&char_events)
+        .map(|e| e.char)
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>();
+    if entry.active {
+        entry.buffer.push_str(&typed);
+        if keyboard_input.just_pressed(KeyCode::Back) {
+            entry.buffer.pop();
+        }
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            entry.active = false;
+            regenerate_events.send(RegenerateEvent);
+            return;
+        }
+        if keyboard_input.just_pressed(KeyCode::Return) {
+            entry.active = false;
+            match parse_maze_code(&entry.buffer) {
+                Ok(parsed) => *code = parsed,
+                Err(e) => eprintln!("invalid maze code: {}", e),
+            }
+            regenerate_events.send(RegenerateEvent);
+            return;
+        }
+        windows
+            .get_primary_mut()
+            .unwrap()
+            .set_title(format!("maze code: {}_", entry.buffer));
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        entry.active = true;
+        entry.buffer.clear();
+        windows
+            .get_primary_mut()
+            .unwrap()
+            .set_title("maze code: _".to_string());
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        code.seed = random_seed();
+        regenerate_events.send(RegenerateEvent);
+    }
+    // Switching algorithms keeps the seed, so that they can be compared on equal terms.
+    let current = Algorithm::ALL
+        .iter()
+        .position(|a| *a == code.algorithm)
+        .unwrap();
+    let mut chosen = current;
+    if keyboard_input.just_pressed(KeyCode::G) {
+        chosen = (current + 1) % Algorithm::ALL.len();
+    }
+    for (i, key) in [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3]
+        .iter()
+        .enumerate()
+    {
+        if keyboard_input.just_pressed(*key) {
+            chosen = i;
+        }
+    }
+    if chosen != current {
+        code.algorithm = Algorithm::ALL[chosen];
+        regenerate_events.send(RegenerateEvent);
+    }
+    let resize = |size: usize, key_down: KeyCode, key_up: KeyCode| {
+        if keyboard_input.just_pressed(key_down) {
+            size.max(2) - 1
+        } else if keyboard_input.just_pressed(key_up) {
+            (size + 1).min(MAX_SIZE)
+        } else {
+            size
+        }
+    };
+    let rows = resize(code.rows, KeyCode::Minus, KeyCode::Equals);
+    let columns = resize(code.columns, KeyCode::LBracket, KeyCode::RBracket);
+    if (rows, columns) != (code.rows, code.columns) && has_goal(rows, columns) {
+        code.rows = rows;
+        code.columns = columns;
+        regenerate_events.send(RegenerateEvent);
+    }
+    if keyboard_input.just_pressed(KeyCode::H) {
+        heat_map_events.send(ToggleHeatMapEvent);
+    }
+    if keyboard_input.just_pressed(KeyCode::F) {
+        fog_events.send(ToggleFogEvent);
+    }
+    if keyboard_input.just_pressed(KeyCode::Z) {
+        fit_events.send(FitAllEvent);
+    }
+    if keyboard_input.just_pressed(KeyCode::M) {
+        minimap_events.send(ToggleMinimapEvent);
+    }
+    if keyboard_input.just_pressed(KeyCode::T) {
+        theme_events.send(NextThemeEvent);
+    }
+    if keyboard_input.just_pressed(KeyCode::V) {
+        animation.enabled = !animation.enabled;
+        if animation.enabled {
+            regenerate_events.send(RegenerateEvent);
+        } else {
+            // Play whatever is left in a single frame.
+            animation.pending = animation.steps.len() as f32;
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Comma) {
+        animation.steps_per_second = (animation.steps_per_second / 2.).max(MIN_STEPS_PER_SECOND);
+    }
+    if keyboard_input.just_pressed(KeyCode::Period) {
+        animation.steps_per_second = (animation.steps_per_second * 2.).min(MAX_STEPS_PER_SECOND);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{convert, parse_color, parse_maze_code};
+    use bevy::prelude::Color;
+    use maze::algorithms::Algorithm;
+    use maze::maze_code::MazeCode;
+
+    fn check((x, y): (isize, isize), size: (f32, f32), (x2, y2): (isize, isize)) {
+        assert_eq!(
+            convert((x as f32, y as f32), 100., size),
+            (x2 as f32, y2 as f32)
+        );
+    }
+
+    #[test]
+    fn test_convert_pos() {
+        check((0, 0), (4., 4.), (-150, 150));
+        check((0, 1), (4., 4.), (-150, 50));
+        check((1, 0), (4., 4.), (-50, 150));
+        check((0, 0), (2., 4.), (-50, 150));
+        check((1, 3), (2., 4.), (50, -150));
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff8000"), Ok(Color::rgb_u8(255, 128, 0)));
+        assert_eq!(parse_color("000000"), Ok(Color::BLACK));
+        assert!(parse_color("fff").is_err());
+        assert!(parse_color("gg0000").is_err());
+    }
+
+    #[test]
+    fn test_parse_maze_code() {
+        let code = |rows, columns| {
+            MazeCode {
+                algorithm: Algorithm::Sidewinder,
+                rows,
+                columns,
+                seed: 1,
+            }
+            .to_string()
+        };
+        assert!(parse_maze_code(&code(1, 2)).is_ok());
+        assert_eq!(
+            parse_maze_code(&code(1, 1)),
+            Err("maze code is for a 1x1 maze, which has no room for a goal".to_string())
+        );
+        assert!(parse_maze_code(&code(101, 2)).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::fog::Fog;
+use crate::player::{Level, Player};
+use crate::{Animation, MazeReadyEvent, Position};
+use bevy::prelude::*;
+use bevy::render::texture::{Extent3d, FilterMode, TextureDimension, TextureFormat};
+use image::{Rgba, RgbaImage};
+use maze::grid::Grid;
+use maze::raster::RenderOptions;
+
+/// The longer side of the minimap on screen, in pixels.
+const MINIMAP_SIZE: f32 = 100.;
+const MARGIN: f32 = 8.;
+
+const WALL: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const EXPLORED: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const UNEXPLORED: Rgba<u8> = Rgba([190, 190, 190, 255]);
+const HIDDEN: Rgba<u8> = Rgba([13, 13, 20, 255]);
+const PLAYER: Rgba<u8> = Rgba([25, 80, 230, 255]);
+const GOAL: Rgba<u8> = Rgba([25, 180, 50, 255]);
+
+/// One pixel for every cell and every wall, drawn by `Grid::to_image` so that the walls are
+/// derived exactly as for the main view's sprites.
+const OPTIONS: RenderOptions = RenderOptions {
+    cell_size: 2,
+    wall_thickness: 1,
+    margin: 0,
+    background: EXPLORED,
+    wall: WALL,
+    heat_map: None,
+    gradient: maze::distances::Gradient {
+        near: EXPLORED,
+        far: EXPLORED,
+    },
+};
+
+/// The cells touching pixel `p` along one axis, and how many there are: one for a cell's own
+/// pixel, the two on either side of a wall pixel. Called for every pixel on every redraw, so it
+/// doesn't allocate.
+fn touching(p: u32) -> ([isize; 2], usize) {
+    let p = p as isize;
+    if p % 2 == 1 {
+        ([(p - 1) / 2, 0], 1)
+    } else {
+        ([p / 2 - 1, p / 2], 2)
+    }
+}
+
+/// Draws the whole maze, shading the cells the player hasn't explored yet. Under fog the walls
+/// around unexplored cells are hidden too.
+fn minimap_image(
+    grid: &Grid,
+    fog: &Fog,
+    player: Option<(isize, isize)>,
+    goal: (isize, isize),
+) -> RgbaImage {
+    let mut image = grid.to_image(&OPTIONS);
+    let is_explored = |x: isize, y: isize| {
+        x >= 0
+            && y >= 0
+            && (x as usize) < grid.columns()
+            && (y as usize) < grid.rows()
+            && fog.is_explored((x, y))
+    };
+    for (px, py, pixel) in image.enumerate_pixels_mut() {
+        let (xs, x_count) = touching(px);
+        let (ys, y_count) = touching(py);
+        let explored = xs[..x_count]
+            .iter()
+            .any(|x| ys[..y_count].iter().any(|y| is_explored(*x, *y)));
+        if explored {
+            continue;
+        }
+        if fog.enabled {
+            *pixel = HIDDEN;
+        } else if *pixel == EXPLORED {
+            *pixel = UNEXPLORED;
+        }
+    }
+    let mut mark = |(x, y): (isize, isize), color| {
+        if grid.cell((x, y)).is_some() && (!fog.enabled || fog.is_explored((x, y))) {
+            image.put_pixel(x as u32 * 2 + 1, y as u32 * 2 + 1, color);
+        }
+    };
+    mark(goal, GOAL);
+    if let Some(player) = player {
+        mark(player, PLAYER);
+    }
+    image
+}
+
+/// The minimap's texture, and whether it is shown.
+pub struct Minimap {
+    enabled: bool,
+    texture: Handle<Texture>,
+}
+
+/// Marks the UI image showing the minimap.
+struct MinimapImage;
+
+pub struct ToggleMinimapEvent;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<ToggleMinimapEvent>()
+            .add_startup_system(spawn_minimap.system())
+            // After the fog has taken in the player's latest move.
+            .add_system_to_stage(stage::LAST, minimap_system.system());
+    }
+}
+
+fn spawn_minimap(
+    commands: &mut Commands,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let texture = textures.add(Texture::default());
+    commands
+        .spawn(ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Px(MARGIN),
+                    bottom: Val::Px(MARGIN),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            material: materials.add(texture.clone().into()),
+            ..Default::default()
+        })
+        .with(MinimapImage);
+    commands.insert_resource(Minimap {
+        enabled: true,
+        texture,
+    });
+}
+
+/// Redraws the minimap whenever the maze, the player or the fog changes.
+fn minimap_system(
+    mut ready_reader: Local<EventReader<MazeReadyEvent>>,
+    mut toggle_reader: Local<EventReader<ToggleMinimapEvent>>,
+    mut last_fog: Local<bool>,
+    ready_events: Res<Events<MazeReadyEvent>>,
+    toggle_events: Res<Events<ToggleMinimapEvent>>,
+    grid: Res<Grid>,
+    fog: Res<Fog>,
+    level: Res<Level>,
+    animation: Res<Animation>,
+    mut minimap: ResMut<Minimap>,
+    mut textures: ResMut<Assets<Texture>>,
+    players: Query<&Position, With<Player>>,
+    moved: Query<&Position, (With<Player>, Changed<Position>)>,
+    mut images: Query<(&mut Style, &mut Visible), With<MinimapImage>>,
+) {
+    let toggled = toggle_reader.iter(&toggle_events).count() % 2 == 1;
+    if toggled {
+        minimap.enabled = !minimap.enabled;
+    }
+    let ready = ready_reader.iter(&ready_events).next().is_some();
+    let redraw = ready
+        || toggled
+        || animation.is_running()
+        || moved.iter().next().is_some()
+        || fog.enabled != *last_fog;
+    if !redraw {
+        return;
+    }
+    *last_fog = fog.enabled;
+
+    let player = players.iter().next().map(|pos| pos.cell());
+    let image = minimap_image(&grid, &fog, player, level.goal);
+    let (width, height) = image.dimensions();
+    let mut texture = Texture::new(
+        Extent3d::new(width, height, 1),
+        TextureDimension::D2,
+        image.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    texture.sampler.mag_filter = FilterMode::Nearest;
+    texture.sampler.min_filter = FilterMode::Nearest;
+    if let Some(old) = textures.get_mut(&minimap.texture) {
+        *old = texture;
+    }
+
+    let scale = MINIMAP_SIZE / width.max(height) as f32;
+    for (mut style, mut visible) in images.iter_mut() {
+        style.size = Size::new(
+            Val::Px(width as f32 * scale),
+            Val::Px(height as f32 * scale),
+        );
+        visible.is_visible = minimap.enabled;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minimap_image, touching, GOAL, HIDDEN, PLAYER, UNEXPLORED, WALL};
+    use crate::fog::Fog;
+    use maze::grid::Grid;
+
+    #[test]
+    fn test_minimap_image() {
+        assert_eq!(touching(0), ([-1, 0], 2));
+        assert_eq!(touching(3), ([1, 0], 1));
+        assert_eq!(touching(4), ([1, 2], 2));
+
+        let grid = "\
++---+---+---+
+|           |
++---+---+   +
+|           |
++---+---+---+
+"
+        .parse::<Grid>()
+        .unwrap();
+        let mut fog = Fog::new(false);
+        fog.look(&grid, (0, 0));
+
+        let image = minimap_image(&grid, &fog, Some((0, 0)), (0, 1));
+        assert_eq!(image.dimensions(), (7, 5));
+        assert_eq!(*image.get_pixel(1, 1), PLAYER);
+        assert_eq!(*image.get_pixel(1, 3), GOAL);
+        assert_eq!(*image.get_pixel(1, 2), WALL);
+        assert_eq!(*image.get_pixel(3, 3), UNEXPLORED);
+
+        fog.enabled = true;
+        let image = minimap_image(&grid, &fog, Some((0, 0)), (0, 1));
+        assert_eq!(*image.get_pixel(1, 3), HIDDEN);
+        // The wall between explored (0, 0) and unexplored (0, 1) stays in view.
+        assert_eq!(*image.get_pixel(1, 2), WALL);
+        assert_eq!(*image.get_pixel(3, 4), HIDDEN);
+    }
+}
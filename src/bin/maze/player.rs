@@ -0,0 +1,283 @@
+use crate::{
+    random_seed, Animation, Atlases, CodeEntry, DrawOffset, MazeReadyEvent, Position,
+    RegenerateEvent, TileScale,
+};
+use bevy::app::startup_stage;
+use bevy::prelude::*;
+use maze::grid::{Direction, Grid};
+use maze::maze_code::MazeCode;
+use std::f32::consts::PI;
+
+/// Where every level starts. The goal is the cell farthest from it.
+pub const START: (isize, isize) = (0, 0);
+
+const PLAYER_COLOR: Color = Color::rgb_linear(0.1, 0.3, 0.9);
+const GOAL_COLOR: Color = Color::rgb_linear(0.1, 0.7, 0.2);
+/// How long walking into a wall takes, and how far towards the wall the player gets, in cells.
+const BUMP_SECONDS: f32 = 0.15;
+const BUMP_DISTANCE: f32 = 0.2;
+
+/// The arrow key and WASD key for each direction.
+const KEYS: [(KeyCode, KeyCode, Direction); 4] = [
+    (KeyCode::Up, KeyCode::W, Direction::North),
+    (KeyCode::Down, KeyCode::S, Direction::South),
+    (KeyCode::Left, KeyCode::A, Direction::West),
+    (KeyCode::Right, KeyCode::D, Direction::East),
+];
+
+pub struct Player;
+
+/// How many cells per second the player slides.
+pub struct MoveSpeed(pub f32);
+
+/// The player's animation between cells. `Position` changes as soon as a move starts, and the
+/// `DrawOffset` eases from the previous cell to zero.
+#[derive(Default)]
+struct Motion {
+    kind: Option<MotionKind>,
+    /// From 0 at the start of the motion to 1 at its end.
+    progress: f32,
+    /// A direction pressed during the current motion, taken once it finishes.
+    buffered: Option<Direction>,
+}
+
+#[derive(Copy, Clone)]
+enum MotionKind {
+    /// Sliding into the current cell from `from`, relative to it.
+    Slide { from: Vec2 },
+    /// Walking into a wall and back.
+    Bump { towards: Vec2 },
+}
+
+impl Motion {
+    fn offset(&self) -> Vec2 {
+        match self.kind {
+            Some(MotionKind::Slide { from }) => {
+                let t = self.progress;
+                from * (1. - t * t * (3. - 2. * t))
+            }
+            Some(MotionKind::Bump { towards }) => {
+                towards * BUMP_DISTANCE * (self.progress * PI).sin()
+            }
+            None => Vec2::zero(),
+        }
+    }
+}
+
+/// The unit step towards `direction` in cell coordinates, where y grows southwards.
+fn step(direction: Direction) -> Vec2 {
+    match direction {
+        Direction::North => Vec2::new(0., -1.),
+        Direction::South => Vec2::new(0., 1.),
+        Direction::East => Vec2::new(1., 0.),
+        Direction::West => Vec2::new(-1., 0.),
+    }
+}
+
+/// Marks the sprite drawn on the goal cell.
+pub struct Goal;
+
+/// The goal of the current level.
+pub struct Level {
+    pub goal: (isize, isize),
+}
+
+/// Sent when the player starts moving to the next cell.
+pub struct PlayerMovedEvent;
+
+/// Sent when the player reaches the goal.
+pub struct LevelCompleteEvent;
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(Level { goal: START })
+            .add_event::<PlayerMovedEvent>()
+            .add_event::<LevelCompleteEvent>()
+            .add_startup_system_to_stage(startup_stage::POST_STARTUP, spawn_player.system())
+            .add_system(start_level.system())
+            .add_system(move_player.system())
+            .add_system(next_level.system());
+    }
+}
+
+fn spawn_player(commands: &mut Commands, atlases: Res<Atlases>) {
+    commands
+        .spawn(SpriteSheetBundle {
+            texture_atlas: atlases.floor.clone(),
+            sprite: TextureAtlasSprite {
+                color: GOAL_COLOR,
+                index: 0,
+            },
+            transform: Transform::from_translation(Vec3::new(0., 0., 0.5)),
+            ..Default::default()
+        })
+        .with(Position::default())
+        .with(TileScale(0.6))
+        .with(Goal);
+    commands
+        .spawn(SpriteSheetBundle {
+            texture_atlas: atlases.floor.clone(),
+            sprite: TextureAtlasSprite {
+                color: PLAYER_COLOR,
+                index: 0,
+            },
+            transform: Transform::from_translation(Vec3::new(0., 0., 2.)),
+            ..Default::default()
+        })
+        .with(Position::default())
+        .with(TileScale(0.5))
+        .with(DrawOffset::default())
+        .with(Motion::default())
+        .with(Player);
+}
+
+/// Puts the player back on `START` and the goal on the farthest cell once a maze is ready.
+fn start_level(
+    mut ready_reader: Local<EventReader<MazeReadyEvent>>,
+    ready_events: Res<Events<MazeReadyEvent>>,
+    grid: Res<Grid>,
+    mut level: ResMut<Level>,
+    mut positions: QuerySet<(
+        Query<(&mut Position, &mut DrawOffset, &mut Motion), With<Player>>,
+        Query<&mut Position, With<Goal>>,
+    )>,
+) {
+    if ready_reader.iter(&ready_events).next().is_none() {
+        return;
+    }
+    level.goal = grid.distances(START).max().0;
+    for (mut pos, mut offset, mut motion) in positions.q0_mut().iter_mut() {
+        *pos = Position::from(START);
+        *offset = DrawOffset::default();
+        *motion = Motion::default();
+    }
+    for mut pos in positions.q1_mut().iter_mut() {
+        *pos = Position::from(level.goal);
+    }
+}
+
+/// Starts a move when a direction key is pressed or held, once any previous move has finished.
+/// Keys pressed during a move are buffered; walking into a wall bumps against it instead.
+fn move_player(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    entry: Res<CodeEntry>,
+    animation: Res<Animation>,
+    grid: Res<Grid>,
+    level: Res<Level>,
+    speed: Res<MoveSpeed>,
+    mut moved_events: ResMut<Events<PlayerMovedEvent>>,
+    mut complete_events: ResMut<Events<LevelCompleteEvent>>,
+    mut players: Query<(&mut Position, &mut DrawOffset, &mut Motion), With<Player>>,
+) {
+    if entry.active || animation.is_running() {
+        return;
+    }
+    let find = |down: &dyn Fn(KeyCode) -> bool| {
+        KEYS.iter()
+            .find(|(arrow, letter, _)| down(*arrow) || down(*letter))
+            .map(|(_, _, direction)| *direction)
+    };
+    let pressed = find(&|key| keyboard_input.just_pressed(key));
+    let held = find(&|key| keyboard_input.pressed(key));
+
+    for (mut pos, mut offset, mut motion) in players.iter_mut() {
+        if pressed.is_some() {
+            motion.buffered = pressed;
+        }
+        if let Some(kind) = motion.kind {
+            let duration = match kind {
+                MotionKind::Slide { .. } => 1. / speed.0,
+                MotionKind::Bump { .. } => BUMP_SECONDS,
+            };
+            motion.progress += time.delta_seconds() / duration;
+            if motion.progress < 1. {
+                offset.0 = motion.offset();
+                continue;
+            }
+            motion.kind = None;
+            offset.0 = Vec2::zero();
+            if let MotionKind::Slide { .. } = kind {
+                if pos.cell() == level.goal {
+                    complete_events.send(LevelCompleteEvent);
+                    continue;
+                }
+            }
+        }
+
+        // A buffered press may bump into a wall; merely holding a key against one doesn't.
+        let (direction, may_bump) = match (motion.buffered.take(), held) {
+            (Some(direction), _) => (direction, true),
+            (None, Some(direction)) => (direction, false),
+            (None, None) => continue,
+        };
+        let cell = match grid.cell(pos.cell()) {
+            Some(cell) => cell,
+            None => continue,
+        };
+        match cell.neighbor(direction).filter(|_| cell.is_open(direction)) {
+            Some(next) => {
+                *pos = Position::from(next);
+                moved_events.send(PlayerMovedEvent);
+                motion.kind = Some(MotionKind::Slide {
+                    from: -step(direction),
+                });
+            }
+            None if may_bump => {
+                motion.kind = Some(MotionKind::Bump {
+                    towards: step(direction),
+                });
+            }
+            None => continue,
+        }
+        motion.progress = 0.;
+        offset.0 = motion.offset();
+    }
+}
+
+fn next_level(
+    mut complete_reader: Local<EventReader<LevelCompleteEvent>>,
+    complete_events: Res<Events<LevelCompleteEvent>>,
+    mut code: ResMut<MazeCode>,
+    mut regenerate_events: ResMut<Events<RegenerateEvent>>,
+) {
+    if complete_reader.iter(&complete_events).next().is_some() {
+        code.seed = random_seed();
+        regenerate_events.send(RegenerateEvent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{step, Motion, MotionKind, BUMP_DISTANCE};
+    use bevy::prelude::Vec2;
+    use maze::grid::Direction;
+
+    fn offset_at(kind: MotionKind, progress: f32) -> Vec2 {
+        Motion {
+            kind: Some(kind),
+            progress,
+            buffered: None,
+        }
+        .offset()
+    }
+
+    #[test]
+    fn test_motion_offset() {
+        let slide = MotionKind::Slide {
+            from: -step(Direction::East),
+        };
+        assert_eq!(offset_at(slide, 0.), Vec2::new(-1., 0.));
+        assert_eq!(offset_at(slide, 0.5), Vec2::new(-0.5, 0.));
+        assert_eq!(offset_at(slide, 1.), Vec2::zero());
+
+        let bump = MotionKind::Bump {
+            towards: step(Direction::North),
+        };
+        assert_eq!(offset_at(bump, 0.), Vec2::zero());
+        assert!((offset_at(bump, 0.5) - Vec2::new(0., -BUMP_DISTANCE)).length() < 1e-6);
+        assert!(offset_at(bump, 1.).length() < 1e-6);
+    }
+}
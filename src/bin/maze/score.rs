@@ -0,0 +1,290 @@
+use crate::player::{LevelCompleteEvent, PlayerMovedEvent, START};
+use crate::{MazeReadyEvent, FONT, HUD_LINE_HEIGHT};
+use anyhow::Context;
+use bevy::prelude::*;
+use maze::grid::Grid;
+use maze::maze_code::MazeCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Moves and time spent on the current level so far.
+#[derive(Default)]
+pub struct LevelStats {
+    /// The code of the maze being played, which its records are kept under.
+    key: String,
+    /// The fewest moves that reach the goal.
+    par: usize,
+    moves: usize,
+    started: f64,
+    /// When the goal was reached, which stops the clock until the next maze is ready.
+    finished: Option<f64>,
+}
+
+impl LevelStats {
+    /// Seconds spent on the level at time `now`.
+    fn seconds(&self, now: f64) -> f64 {
+        self.finished.unwrap_or(now) - self.started
+    }
+}
+
+/// How a level was completed.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub moves: usize,
+    pub seconds: f32,
+    pub stars: u8,
+}
+
+impl Record {
+    /// Fewer moves win; time breaks ties.
+    fn beats(&self, other: &Record) -> bool {
+        (self.moves, self.seconds) < (other.moves, other.seconds)
+    }
+}
+
+/// Three stars for reaching the goal in par, two for at most half as many moves again, one
+/// otherwise.
+pub fn stars(moves: usize, par: usize) -> u8 {
+    if moves <= par {
+        3
+    } else if moves * 2 <= par * 3 {
+        2
+    } else {
+        1
+    }
+}
+
+/// The best record for every maze played, saved as JSON so that players can compete on the same
+/// maze code.
+#[derive(Default)]
+pub struct Scores {
+    /// Where the records are saved. The default, with no file, keeps them for this session only.
+    path: Option<PathBuf>,
+    best: HashMap<String, Record>,
+    /// The previous level's result, shown while the next one is played.
+    last: Option<Record>,
+}
+
+impl Scores {
+    /// Reads the records saved at `path`. A missing file means nothing has been played yet.
+    pub fn load(path: impl Into<PathBuf>) -> anyhow::Result<Scores> {
+        let path = path.into();
+        let best = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("can't parse {}", path.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e).with_context(|| format!("can't read {}", path.display())),
+        };
+        Ok(Scores {
+            path: Some(path),
+            best,
+            last: None,
+        })
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let text = serde_json::to_string_pretty(&self.best)?;
+        fs::write(path, text).with_context(|| format!("can't write {}", path.display()))
+    }
+
+    pub fn best(&self, key: &str) -> Option<Record> {
+        self.best.get(key).copied()
+    }
+
+    /// Keeps `record` if it is the first or best for `key`, returning whether it was.
+    fn add(&mut self, key: &str, record: Record) -> bool {
+        match self.best.get(key) {
+            Some(best) if !record.beats(best) => false,
+            _ => {
+                self.best.insert(key.to_string(), record);
+                true
+            }
+        }
+    }
+}
+
+/// Marks the text line showing the current level's moves and time.
+struct ScoreHud;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(LevelStats::default())
+            .add_startup_system(spawn_score_hud.system())
+            .add_system_to_stage(stage::POST_UPDATE, score_system.system())
+            .add_system(score_hud_system.system());
+    }
+}
+
+fn spawn_score_hud(commands: &mut Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(TextBundle {
+            text: Text {
+                value: String::new(),
+                font: asset_server.load(FONT),
+                style: TextStyle {
+                    font_size: 16.,
+                    color: Color::BLACK,
+                    ..Default::default()
+                },
+            },
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(4. + HUD_LINE_HEIGHT),
+                    left: Val::Px(8.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with(ScoreHud);
+}
+
+/// Counts moves, starts the clock on every new maze and records the result when the goal is
+/// reached.
+fn score_system(
+    time: Res<Time>,
+    code: Res<MazeCode>,
+    grid: Res<Grid>,
+    mut complete_reader: Local<EventReader<LevelCompleteEvent>>,
+    mut ready_reader: Local<EventReader<MazeReadyEvent>>,
+    mut moved_reader: Local<EventReader<PlayerMovedEvent>>,
+    complete_events: Res<Events<LevelCompleteEvent>>,
+    ready_events: Res<Events<MazeReadyEvent>>,
+    moved_events: Res<Events<PlayerMovedEvent>>,
+    mut stats: ResMut<LevelStats>,
+    mut scores: ResMut<Scores>,
+) {
+    // Completing a level and the next one being ready can arrive together, so finish the old
+    // level before starting the new one.
+    if complete_reader.iter(&complete_events).next().is_some() {
+        stats.finished = Some(time.seconds_since_startup());
+        let record = Record {
+            moves: stats.moves,
+            seconds: stats.seconds(time.seconds_since_startup()) as f32,
+            stars: stars(stats.moves, stats.par),
+        };
+        if scores.add(&stats.key, record) {
+            if let Err(e) = scores.save() {
+                eprintln!("can't save scores: {:#}", e);
+            }
+        }
+        scores.last = Some(record);
+    }
+    if ready_reader.iter(&ready_events).next().is_some() {
+        // `Level` only moves its goal on the next frame, so work out the goal's distance here
+        // the same way `start_level` picks it: the farthest cell from `START`.
+        *stats = LevelStats {
+            key: code.to_string(),
+            par: grid.distances(START).max().1,
+            moves: 0,
+            started: time.seconds_since_startup(),
+            finished: None,
+        };
+    }
+    stats.moves += moved_reader.iter(&moved_events).count();
+}
+
+fn score_text(stats: &LevelStats, seconds: f64, scores: &Scores) -> String {
+    let mut text = format!("moves {}/{}  {:.0}s", stats.moves, stats.par, seconds);
+    if let Some(best) = scores.best(&stats.key) {
+        text.push_str(&format!("  best {}", best.moves));
+    }
+    if let Some(last) = scores.last {
+        let stars = (0..3)
+            .map(|i| if i < last.stars { '★' } else { '☆' })
+            .collect::<String>();
+        text.push_str(&format!("  last {}", stars));
+    }
+    text
+}
+
+fn score_hud_system(
+    time: Res<Time>,
+    stats: Res<LevelStats>,
+    scores: Res<Scores>,
+    mut query: Query<&mut Text, With<ScoreHud>>,
+) {
+    let seconds = stats.seconds(time.seconds_since_startup());
+    let value = score_text(&stats, seconds.floor(), &scores);
+    for mut text in query.iter_mut() {
+        if text.value != value {
+            text.value = value.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{score_text, stars, LevelStats, Record, Scores};
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_stars() {
+        assert_eq!(stars(10, 10), 3);
+        assert_eq!(stars(11, 10), 2);
+        assert_eq!(stars(15, 10), 2);
+        assert_eq!(stars(16, 10), 1);
+        assert_eq!(stars(0, 0), 3);
+    }
+
+    #[test]
+    fn test_scores() {
+        let path = env::temp_dir().join(format!("maze-scores-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut scores = Scores::load(&path).unwrap();
+        let record = Record {
+            moves: 12,
+            seconds: 9.5,
+            stars: 2,
+        };
+        assert!(scores.add("S10x10-1", record));
+        assert!(!scores.add(
+            "S10x10-1",
+            Record {
+                moves: 12,
+                seconds: 11.,
+                ..record
+            }
+        ));
+        let faster = Record {
+            seconds: 8.,
+            ..record
+        };
+        assert!(scores.add("S10x10-1", faster));
+        scores.save().unwrap();
+
+        let mut scores = Scores::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(scores.best("S10x10-1"), Some(faster));
+        assert_eq!(scores.best("S10x10-2"), None);
+
+        let mut stats = LevelStats {
+            key: "S10x10-1".to_string(),
+            par: 10,
+            moves: 4,
+            started: 2.,
+            finished: None,
+        };
+        assert_eq!(stats.seconds(5.), 3.);
+        stats.finished = Some(4.5);
+        assert_eq!(stats.seconds(9.), 2.5);
+        assert_eq!(score_text(&stats, 3., &scores), "moves 4/10  3s  best 12");
+        scores.last = Some(record);
+        assert_eq!(
+            score_text(&stats, 3., &scores),
+            "moves 4/10  3s  best 12  last ★★☆"
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use crate::walls::Walls;
+use crate::{Animation, MazeReadyEvent, Position, TileScale, SPRITE_SIZE};
+use anyhow::{bail, Context};
+use bevy::prelude::*;
+use bevy::render::texture::FilterMode;
+use maze::grid::Grid;
+use maze::walls::TILE_WALLS;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The theme drawing the walls as a mesh instead of a tileset.
+pub const LINES: &str = "lines";
+
+/// A tileset's RON descriptor.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TilesetDescriptor {
+    pub name: String,
+    /// The texture, relative to the descriptor.
+    pub texture: PathBuf,
+    /// The width and height of a tile in pixels.
+    pub tile_size: f32,
+    pub columns: usize,
+    pub rows: usize,
+    /// The tile for each `Grid::tile_mask`. A mask that isn't listed gets the tile for its walls
+    /// without the corners, or tile 0. Without a table, the tile is the mask itself.
+    #[serde(default)]
+    pub tiles: Option<HashMap<u8, u32>>,
+}
+
+impl TilesetDescriptor {
+    pub fn tile(&self, mask: u8) -> u32 {
+        match &self.tiles {
+            Some(tiles) => tiles
+                .get(&mask)
+                .or_else(|| tiles.get(&(mask & TILE_WALLS)))
+                .copied()
+                .unwrap_or(0),
+            None => u32::from(mask),
+        }
+    }
+
+    /// Checks that tiles have a size, and that every tile `tile` can pick is in the texture.
+    fn validate(&self) -> anyhow::Result<()> {
+        if !(self.tile_size > 0. && self.tile_size.is_finite()) {
+            bail!("tile_size must be positive, not {}", self.tile_size);
+        }
+        let count = self.columns.saturating_mul(self.rows);
+        if let Some(mask) = (0..=u8::MAX).find(|&mask| self.tile(mask) as usize >= count) {
+            bail!(
+                "tile {} for mask {:#010b} is outside the {}x{} texture",
+                self.tile(mask),
+                mask,
+                self.columns,
+                self.rows
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Reads every `*.ron` descriptor in `dir`, sorted by name, with its texture path resolved
+/// against `dir`. Descriptors that can't be read are skipped with a warning, so that one broken
+/// file doesn't take the others with it.
+pub fn load_tilesets(dir: &Path) -> anyhow::Result<Vec<TilesetDescriptor>> {
+    let mut tilesets = vec![];
+    let entries = fs::read_dir(dir).with_context(|| format!("can't read {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension() != Some(OsStr::new("ron")) {
+            continue;
+        }
+        match load_tileset(&path) {
+            Ok(mut tileset) => {
+                tileset.texture = dir.join(&tileset.texture);
+                tilesets.push(tileset);
+            }
+            Err(e) => eprintln!("skipping tileset: {:#}", e),
+        }
+    }
+    tilesets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tilesets)
+}
+
+fn load_tileset(path: &Path) -> anyhow::Result<TilesetDescriptor> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("can't read {}", path.display()))?;
+    let tileset: TilesetDescriptor =
+        ron::de::from_str(&text).with_context(|| format!("can't parse {}", path.display()))?;
+    tileset
+        .validate()
+        .with_context(|| format!("invalid {}", path.display()))?;
+    Ok(tileset)
+}
+
+struct Tileset {
+    descriptor: TilesetDescriptor,
+    texture: Handle<Texture>,
+    atlas: Handle<TextureAtlas>,
+}
+
+/// The wall themes to choose from: the mesh drawn by `walls::WallsPlugin`, then every tileset.
+pub struct Themes {
+    descriptors: Vec<TilesetDescriptor>,
+    tilesets: Vec<Tileset>,
+    /// 0 for the mesh, or one more than the tileset's index.
+    current: usize,
+}
+
+impl Themes {
+    /// Starts on the theme called `name`, if there is one.
+    pub fn new(descriptors: Vec<TilesetDescriptor>, name: &str) -> Result<Themes, String> {
+        let current = match descriptors.iter().position(|d| d.name == name) {
+            Some(i) => i + 1,
+            None if name == LINES => 0,
+            None => {
+                let names = descriptors
+                    .iter()
+                    .map(|d| d.name.as_str())
+                    .collect::<Vec<_>>();
+                return Err(format!(
+                    "unknown theme {}, expected {} or one of [{}]",
+                    name,
+                    LINES,
+                    names.join(", ")
+                ));
+            }
+        };
+        Ok(Themes {
+            descriptors,
+            tilesets: vec![],
+            current,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        match self.current {
+            0 => LINES,
+            i => &self.descriptors[i - 1].name,
+        }
+    }
+
+    fn tileset(&self) -> Option<&Tileset> {
+        self.current
+            .checked_sub(1)
+            .and_then(|i| self.tilesets.get(i))
+    }
+}
+
+/// Marks the sprite drawing a cell's walls from the current tileset.
+pub struct WallTile;
+
+pub struct NextThemeEvent;
+
+pub struct TilesetPlugin;
+
+impl Plugin for TilesetPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<NextThemeEvent>()
+            .add_startup_system(load_textures.system())
+            .add_system(sharpen_textures.system())
+            .add_system_to_stage(stage::POST_UPDATE, tileset_system.system());
+    }
+}
+
+fn load_textures(
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut themes: ResMut<Themes>,
+) {
+    let tilesets = themes
+        .descriptors
+        .iter()
+        .map(|descriptor| {
+            // The asset server would look for a relative path in `assets`.
+            let path = descriptor
+                .texture
+                .canonicalize()
+                .unwrap_or_else(|_| descriptor.texture.clone());
+            let texture = asset_server.load(path.as_path());
+            let atlas = texture_atlases.add(TextureAtlas::from_grid(
+                texture.clone(),
+                Vec2::splat(descriptor.tile_size),
+                descriptor.columns,
+                descriptor.rows,
+            ));
+            Tileset {
+                descriptor: descriptor.clone(),
+                texture,
+                atlas,
+            }
+        })
+        .collect();
+    themes.tilesets = tilesets;
+}
+
+/// Samples tileset textures without filtering, which would blur the pixels and bleed
+/// neighboring tiles into each other.
+fn sharpen_textures(
+    mut reader: Local<EventReader<AssetEvent<Texture>>>,
+    events: Res<Events<AssetEvent<Texture>>>,
+    themes: Res<Themes>,
+    mut textures: ResMut<Assets<Texture>>,
+) {
+    for event in reader.iter(&events) {
+        if let AssetEvent::Created { handle } = event {
+            if themes.tilesets.iter().any(|t| t.texture == *handle) {
+                if let Some(texture) = textures.get_mut(handle) {
+                    texture.sampler.mag_filter = FilterMode::Nearest;
+                    texture.sampler.min_filter = FilterMode::Nearest;
+                }
+            }
+        }
+    }
+}
+
+/// Switches themes, and picks every cell's tile once a maze is ready, while it is animated and
+/// when new cells are spawned.
+fn tileset_system(
+    mut ready_reader: Local<EventReader<MazeReadyEvent>>,
+    mut next_reader: Local<EventReader<NextThemeEvent>>,
+    ready_events: Res<Events<MazeReadyEvent>>,
+    next_events: Res<Events<NextThemeEvent>>,
+    grid: Res<Grid>,
+    animation: Res<Animation>,
+    mut themes: ResMut<Themes>,
+    mut walls: Query<&mut Visible, With<Walls>>,
+    added: Query<Entity, Added<WallTile>>,
+    mut tiles: Query<
+        (
+            &Position,
+            &mut TextureAtlasSprite,
+            &mut Handle<TextureAtlas>,
+            &mut TileScale,
+        ),
+        With<WallTile>,
+    >,
+) {
+    let switched = next_reader.iter(&next_events).count() % (themes.tilesets.len() + 1);
+    if switched > 0 {
+        themes.current = (themes.current + switched) % (themes.tilesets.len() + 1);
+    }
+    let ready = ready_reader.iter(&ready_events).next().is_some();
+    if switched == 0 && !ready && !animation.is_running() && added.iter().next().is_none() {
+        return;
+    }
+
+    let tileset = themes.tileset();
+    for mut visible in walls.iter_mut() {
+        visible.is_visible = tileset.is_none();
+    }
+    for (pos, mut sprite, mut atlas, mut scale) in tiles.iter_mut() {
+        match tileset {
+            Some(tileset) => {
+                let mask = grid.tile_mask(pos.cell()).unwrap_or(0);
+                sprite.index = tileset.descriptor.tile(mask);
+                sprite.color = Color::WHITE;
+                if *atlas != tileset.atlas {
+                    *atlas = tileset.atlas.clone();
+                    scale.0 = SPRITE_SIZE / tileset.descriptor.tile_size;
+                }
+            }
+            None => sprite.color = Color::NONE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_tilesets, Themes, TilesetDescriptor, LINES};
+    use maze::grid::{WALL_NORTH, WALL_WEST};
+    use maze::walls::CORNER_SOUTH_EAST;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_tile() {
+        let mut descriptor = TilesetDescriptor {
+            name: "test".to_string(),
+            texture: PathBuf::from("test.png"),
+            tile_size: 16.,
+            columns: 16,
+            rows: 16,
+            tiles: None,
+        };
+        let mask = WALL_NORTH | WALL_WEST | CORNER_SOUTH_EAST;
+        assert_eq!(descriptor.tile(mask), u32::from(mask));
+
+        let mut tiles = HashMap::new();
+        tiles.insert(WALL_NORTH | WALL_WEST, 3);
+        tiles.insert(WALL_NORTH, 1);
+        descriptor.tiles = Some(tiles);
+        assert_eq!(descriptor.tile(mask), 3);
+        assert_eq!(descriptor.tile(WALL_NORTH), 1);
+        assert_eq!(descriptor.tile(WALL_WEST), 0);
+    }
+
+    #[test]
+    fn test_bundled_tilesets() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/tilesets");
+        let tilesets = load_tilesets(&dir).unwrap();
+        let names = tilesets.iter().map(|t| t.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["dungeon", "hedge", "sci-fi"]);
+        for tileset in &tilesets {
+            assert!(tileset.texture.is_file(), "{:?}", tileset.texture);
+            assert_eq!(tileset.columns * tileset.rows, 256);
+        }
+
+        let themes = Themes::new(tilesets.clone(), "hedge").unwrap();
+        assert_eq!(themes.name(), "hedge");
+        assert_eq!(Themes::new(tilesets.clone(), LINES).unwrap().name(), LINES);
+        assert_eq!(
+            Themes::new(tilesets, "lava").err().unwrap(),
+            "unknown theme lava, expected lines or one of [dungeon, hedge, sci-fi]"
+        );
+    }
+
+    #[test]
+    fn test_bad_tileset_is_skipped() {
+        let dir = std::env::temp_dir().join(format!("maze-tilesets-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("broken.ron"), "(name: \"broken\"").unwrap();
+        fs::write(
+            dir.join("flat.ron"),
+            "(name: \"flat\", texture: \"flat.png\", tile_size: 0, columns: 16, rows: 16)",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("small.ron"),
+            "(name: \"small\", texture: \"small.png\", tile_size: 8, columns: 4, rows: 4)",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("sparse.ron"),
+            "(name: \"sparse\", texture: \"sparse.png\", tile_size: 8, columns: 4, rows: 4, \
+             tiles: Some({0: 1, 3: 16}))",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("plain.ron"),
+            "(name: \"plain\", texture: \"plain.png\", tile_size: 8, columns: 16, rows: 16)",
+        )
+        .unwrap();
+        let tilesets = load_tilesets(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let tilesets = tilesets.unwrap();
+        assert_eq!(tilesets.len(), 1);
+        assert_eq!(tilesets[0].name, "plain");
+        assert_eq!(tilesets[0].texture, dir.join("plain.png"));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{Animation, MazeReadyEvent, Position, SPRITE_SIZE};
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::pipeline::PrimitiveTopology;
+use maze::grid::Grid;
+
+/// How the walls are drawn.
+pub struct WallStyle {
+    /// As a fraction of a cell.
+    pub thickness: f32,
+    pub color: Color,
+}
+
+/// Marks the entity drawing every wall of the maze as a single mesh.
+pub struct Walls;
+
+/// The mesh of the walls, rebuilt whenever the maze changes.
+struct WallMesh(Handle<Mesh>);
+
+pub struct WallsPlugin;
+
+impl Plugin for WallsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(spawn_walls.system())
+            .add_system_to_stage(stage::POST_UPDATE, walls_system.system());
+    }
+}
+
+/// A quad for every straight run of wall. The mesh is laid out like the cell sprites, in
+/// `SPRITE_SIZE` units per cell around the centre of cell `(0, 0)` with y pointing up, so that
+/// `position_translation` places and scales it like a sprite at that cell.
+fn wall_mesh(grid: &Grid, thickness: f32) -> Mesh {
+    let mut positions = vec![];
+    let mut indices = vec![];
+    let to_sprite = |(x, y): (f32, f32)| [(x - 0.5) * SPRITE_SIZE, (0.5 - y) * SPRITE_SIZE, 0.];
+    for segment in grid.wall_segments() {
+        let ((left, top), (right, bottom)) = segment.rect(thickness);
+        let first = positions.len() as u32;
+        positions.push(to_sprite((left, top)));
+        positions.push(to_sprite((right, top)));
+        positions.push(to_sprite((right, bottom)));
+        positions.push(to_sprite((left, bottom)));
+        indices.extend_from_slice(&[first, first + 2, first + 1, first, first + 3, first + 2]);
+    }
+    let count = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; count]);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; count]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+fn spawn_walls(
+    commands: &mut Commands,
+    style: Res<WallStyle>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let mesh = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
+    commands
+        .spawn(SpriteBundle {
+            mesh: mesh.clone(),
+            material: materials.add(style.color.into()),
+            // The mesh is already sprite sized.
+            sprite: Sprite {
+                size: Vec2::one(),
+                resize_mode: SpriteResizeMode::Manual,
+            },
+            transform: Transform::from_translation(Vec3::new(0., 0., 1.)),
+            ..Default::default()
+        })
+        .with(Position::default())
+        .with(Walls);
+    commands.insert_resource(WallMesh(mesh));
+}
+
+/// Rebuilds the walls once a maze is ready, and on every frame of an animation.
+fn walls_system(
+    mut ready_reader: Local<EventReader<MazeReadyEvent>>,
+    ready_events: Res<Events<MazeReadyEvent>>,
+    grid: Res<Grid>,
+    animation: Res<Animation>,
+    style: Res<WallStyle>,
+    wall_mesh_handle: Res<WallMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let ready = ready_reader.iter(&ready_events).next().is_some();
+    if !ready && !animation.is_running() {
+        return;
+    }
+    if let Some(mesh) = meshes.get_mut(&wall_mesh_handle.0) {
+        *mesh = wall_mesh(&grid, style.thickness);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wall_mesh;
+    use crate::SPRITE_SIZE;
+    use bevy::render::mesh::{Indices, Mesh, VertexAttributeValues};
+    use maze::grid::Grid;
+
+    #[test]
+    fn test_wall_mesh() {
+        let mut grid = Grid::new(1, 2);
+        grid.link_cell((0, 0), (1, 0), true);
+        let mesh = wall_mesh(&grid, 0.5);
+        // Top, bottom, left and right walls.
+        match mesh.indices() {
+            Some(Indices::U32(indices)) => assert_eq!(indices.len(), 4 * 6),
+            _ => panic!("expected u32 indices"),
+        }
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float3(positions)) => {
+                let s = SPRITE_SIZE;
+                assert_eq!(positions.len(), 16);
+                // The top wall spans both cells and overhangs the corners.
+                assert_eq!(positions[0], [-0.75 * s, 0.75 * s, 0.]);
+                assert_eq!(positions[2], [1.75 * s, 0.25 * s, 0.]);
+            }
+            _ => panic!("expected positions"),
+        }
+    }
+}
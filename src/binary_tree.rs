@@ -1,10 +1,22 @@
-use crate::grid::Grid;
-use rand::prelude::SliceRandom;
+use crate::algorithms::Step;
+use crate::grid::{Grid, GridError};
+use crate::random;
+use crate::weave_grid::WeaveGrid;
 use rand::Rng;
 
-pub fn binary_tree<T: Rng>(grid: &mut Grid, rng: &mut T) {
+pub fn binary_tree<T: Rng>(grid: &mut Grid, rng: &mut T) -> Result<(), GridError> {
+    binary_tree_traced(grid, rng, |_| {})
+}
+
+/// Like `binary_tree`, reporting each cell visited and passage carved to `trace`.
+pub fn binary_tree_traced<T: Rng>(
+    grid: &mut Grid,
+    rng: &mut T,
+    mut trace: impl FnMut(Step),
+) -> Result<(), GridError> {
     let mut neighbors = vec![];
     for pos in grid.iter() {
+        trace(Step::Visit(pos));
         neighbors.clear();
         if let Some(north) = grid.north_of_cell(pos) {
             neighbors.push(north);
@@ -12,16 +24,41 @@ pub fn binary_tree<T: Rng>(grid: &mut Grid, rng: &mut T) {
         if let Some(east) = grid.east_of_cell(pos) {
             neighbors.push(east);
         }
-        let neighbor = neighbors.choose(rng);
+        let neighbor = random::choose(rng, &neighbors);
         if let Some(neighbor) = neighbor.cloned() {
-            grid.link_cell(pos, neighbor, true);
+            grid.try_link(pos, neighbor, true)?;
+            trace(Step::Link(pos, neighbor));
         }
     }
+    Ok(())
+}
+
+/// Like `binary_tree`, but a cell may also tunnel under a finished east-west corridor to its
+/// north. Cells to the east are not carved yet, so eastward tunnels are never offered.
+pub fn binary_tree_weave<T: Rng>(grid: &mut WeaveGrid, rng: &mut T) -> Result<(), GridError> {
+    let mut neighbors = vec![];
+    for pos in grid.iter() {
+        neighbors.clear();
+        if let Some(north) = grid.north_of_cell(pos) {
+            neighbors.push(north);
+        }
+        if let Some(east) = grid.east_of_cell(pos) {
+            neighbors.push(east);
+        }
+        if let Some(tunnel) = grid.can_tunnel_north(pos) {
+            neighbors.push(tunnel);
+        }
+        let neighbor = random::choose(rng, &neighbors);
+        if let Some(neighbor) = neighbor.cloned() {
+            grid.try_link(pos, neighbor)?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::binary_tree::binary_tree;
+    use crate::binary_tree::{binary_tree, binary_tree_weave};
     use expect_test::expect;
 
     #[test]
@@ -31,26 +68,56 @@ mod tests {
             expect![[r#"
                 +---+---+---+---+---+---+---+---+---+---+
                 |                                       |
-                +   +   +---+---+---+---+   +   +   +   +
-                |   |   |                   |   |   |   |
-                +---+---+---+   +---+---+---+   +---+   +
-                |               |               |       |
-                +---+---+---+---+   +---+   +---+---+   +
-                |                   |       |           |
-                +   +---+   +---+---+   +---+   +---+   +
-                |   |       |           |       |       |
-                +---+   +---+---+---+---+   +   +---+   +
-                |       |                   |   |       |
-                +   +---+---+   +---+   +---+   +---+   +
-                |   |           |       |       |       |
+                +---+   +   +   +---+   +   +   +   +   +
+                |       |   |   |       |   |   |   |   |
+                +---+---+   +---+---+---+---+---+---+   +
+                |           |                           |
+                +   +   +   +---+   +---+   +   +---+   +
+                |   |   |   |       |       |   |       |
+                +---+   +   +---+   +---+---+---+   +   +
+                |       |   |       |               |   |
                 +---+---+---+   +   +   +   +---+---+   +
                 |               |   |   |   |           |
-                +---+   +   +   +---+---+---+---+   +   +
-                |       |   |   |                   |   |
-                +---+   +   +   +   +   +   +   +   +   +
-                |       |   |   |   |   |   |   |   |   |
+                +---+   +---+   +---+   +   +---+   +   +
+                |       |       |       |   |       |   |
+                +---+---+   +   +---+---+---+---+---+   +
+                |           |   |                       |
+                +   +   +---+---+   +---+   +   +   +   +
+                |   |   |           |       |   |   |   |
+                +   +   +---+---+   +---+---+   +---+   +
+                |   |   |           |           |       |
                 +---+---+---+---+---+---+---+---+---+---+
             "#]]
         )
     }
+
+    #[test]
+    fn test_binary_tree_weave() {
+        check_weave_alg!(
+            binary_tree_weave,
+            expect![[r#"
+                +---+---+---+---+---+---+---+---+---+---+
+                |                                       |
+                +---+   +   +   +---+   +   +   +   +   +
+                |       |   |   |       |   |   |   |   |
+                +---+---+   +---+---+   +---+---+   +   +
+                |           |        ===         ===    |
+                +   +---+   +---+---+   +   +   +   +   +
+                |   |       |           |   |   |   |   |
+                +---+   +   +---+---+---+---+---+   +   +
+                |       |   |                       |   |
+                +---+---+---+   +   +---+   +---+---+   +
+                |               |   |       |           |
+                +---+---+---+   +---+   +   +---+   +   +
+                |               |       |   |       |   |
+                +---+---+   +   +---+---+---+---+---+   +
+                |        ===    |                       |
+                +   +---+   +---+   +---+   +---+   +   +
+                |   |       |       |       |       |   |
+                +   +   +---+---+   +---+---+   +---+   +
+                |   |   |           |           |       |
+                +---+---+---+---+---+---+---+---+---+---+
+            "#]]
+        );
+    }
 }
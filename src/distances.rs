@@ -0,0 +1,214 @@
+use crate::grid::{Grid, Position};
+use crate::grid3d::{Grid3D, Position3D};
+use crate::weave_grid::WeaveGrid;
+use image::Rgba;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Number of steps from a root cell to every cell reachable from it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distances<P: Eq + Hash = Position> {
+    root: P,
+    cells: HashMap<P, usize>,
+    /// The farthest cell, found once so that colouring every cell stays linear.
+    max: (P, usize),
+}
+
+impl<P: Copy + Eq + Hash> Distances<P> {
+    pub fn root(&self) -> P {
+        self.root
+    }
+
+    pub fn get(&self, pos: P) -> Option<usize> {
+        self.cells.get(&pos).copied()
+    }
+
+    /// The farthest cell from the root and its distance.
+    pub fn max(&self) -> (P, usize) {
+        self.max
+    }
+
+    /// The cell's heat map colour, scaled so that the farthest cell gets `gradient.far`.
+    pub fn color(&self, pos: P, gradient: &Gradient) -> Option<Rgba<u8>> {
+        let distance = self.get(pos)?;
+        let (_, max) = self.max;
+        if max == 0 {
+            return Some(gradient.near);
+        }
+        Some(gradient.at(distance as f32 / max as f32))
+    }
+}
+
+impl Distances {
+    /// Cells on the shortest path from the root to `goal`, both included.
+    pub fn path_to(&self, grid: &Grid, goal: Position) -> Option<Vec<Position>> {
+        let mut current = goal;
+        let mut distance = self.get(goal)?;
+        let mut path = vec![goal];
+        while current != self.root {
+            current = grid
+                .links_of_cell(current)
+                .into_iter()
+                .find(|pos| self.get(*pos) == Some(distance - 1))?;
+            distance -= 1;
+            path.push(current);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Colours for distance heat maps, interpolated from `near` at the root to `far` at the
+/// farthest cell.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Gradient {
+    pub near: Rgba<u8>,
+    pub far: Rgba<u8>,
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Gradient {
+            near: Rgba([250, 240, 180, 255]),
+            far: Rgba([20, 90, 160, 255]),
+        }
+    }
+}
+
+impl Gradient {
+    /// The colour `t` of the way from `near` to `far`, with `t` clamped to `0..=1`.
+    pub fn at(&self, t: f32) -> Rgba<u8> {
+        let t = t.clamp(0., 1.);
+        let mut color = self.near;
+        for i in 0..4 {
+            let near = self.near[i] as f32;
+            let far = self.far[i] as f32;
+            color[i] = (near + (far - near) * t).round() as u8;
+        }
+        color
+    }
+}
+
+impl Grid {
+    /// Breadth-first flood fill from `root` along the links.
+    pub fn distances(&self, root: Position) -> Distances {
+        flood(root, |pos| self.links_of_cell(pos))
+    }
+}
+
+impl WeaveGrid {
+    /// Like `Grid::distances`; passing under a bridge counts as a single step.
+    pub fn distances(&self, root: Position) -> Distances {
+        flood(root, |pos| self.links_of_cell(pos))
+    }
+}
+
+impl Grid3D {
+    /// Like `Grid::distances`; climbing a stair counts as a single step.
+    pub fn distances(&self, root: Position3D) -> Distances<Position3D> {
+        flood(root, |pos| {
+            self.neighbors_of_cell(pos)
+                .into_iter()
+                .filter(|next| self.is_linked(pos, *next))
+                .collect()
+        })
+    }
+}
+
+fn flood<P: Copy + Ord + Hash>(root: P, links_of_cell: impl Fn(P) -> Vec<P>) -> Distances<P> {
+    let mut cells = HashMap::new();
+    let mut frontier = VecDeque::new();
+    cells.insert(root, 0);
+    frontier.push_back(root);
+    while let Some(pos) = frontier.pop_front() {
+        let distance = cells[&pos];
+        for link in links_of_cell(pos) {
+            if let Entry::Vacant(entry) = cells.entry(link) {
+                entry.insert(distance + 1);
+                frontier.push_back(link);
+            }
+        }
+    }
+    let mut max = (root, 0);
+    for (pos, distance) in cells.iter() {
+        if *distance > max.1 || (*distance == max.1 && *pos < max.0) {
+            max = (*pos, *distance);
+        }
+    }
+    Distances { root, cells, max }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::distances::Gradient;
+    use crate::grid::Grid;
+    use crate::test_utils::check_debug;
+    use expect_test::expect;
+    use image::Rgba;
+
+    #[test]
+    fn test_distances() {
+        let grid = "\
++---+---+---+
+|           |
++   +---+   +
+|   |   |   |
++---+---+---+
+"
+        .parse::<Grid>()
+        .unwrap();
+        let distances = grid.distances((0, 1));
+        assert_eq!(distances.get((2, 1)), Some(4));
+        assert_eq!(distances.get((1, 1)), None);
+        assert_eq!(distances.max(), ((2, 1), 4));
+        check_debug(
+            distances.path_to(&grid, (2, 1)),
+            expect![[r#"
+            Some(
+                [
+                    (
+                        0,
+                        1,
+                    ),
+                    (
+                        0,
+                        0,
+                    ),
+                    (
+                        1,
+                        0,
+                    ),
+                    (
+                        2,
+                        0,
+                    ),
+                    (
+                        2,
+                        1,
+                    ),
+                ],
+            )
+        "#]],
+        );
+        assert_eq!(distances.path_to(&grid, (1, 1)), None);
+    }
+
+    #[test]
+    fn test_gradient() {
+        let gradient = Gradient {
+            near: Rgba([0, 0, 0, 255]),
+            far: Rgba([200, 100, 50, 255]),
+        };
+        assert_eq!(gradient.at(0.5), Rgba([100, 50, 25, 255]));
+        assert_eq!(gradient.at(2.), gradient.far);
+
+        let mut grid = Grid::new(1, 3);
+        grid.link_cell((0, 0), (1, 0), true);
+        grid.link_cell((1, 0), (2, 0), true);
+        let distances = grid.distances((0, 0));
+        assert_eq!(distances.color((0, 0), &gradient), Some(gradient.near));
+        assert_eq!(distances.color((1, 0), &gradient), Some(gradient.at(0.5)));
+        assert_eq!(distances.color((2, 0), &gradient), Some(gradient.far));
+    }
+}
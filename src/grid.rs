@@ -1,226 +1,274 @@
-use rand::prelude::SliceRandom;
-use std::collections::HashSet;
+use crate::text::write_box_drawing;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
 use std::fmt;
 
-type Position = (isize, isize);
+pub type Position = (isize, isize);
 
-#[derive(PartialEq, Debug)]
-struct Cell {
-    pub pos: Position,
-    pub north: Option<Position>,
-    pub south: Option<Position>,
-    pub west: Option<Position>,
-    pub east: Option<Position>,
-    links: HashSet<Position>,
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
 }
 
-impl Cell {
-    fn new(pos: Position) -> Self {
-        Cell {
-            pos,
-            north: None,
-            south: None,
-            west: None,
-            east: None,
-            links: HashSet::new(),
-        }
-    }
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+    ];
 
-    fn link(&mut self, position: Position) {
-        self.links.insert(position);
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
     }
 
-    fn unlink(&mut self, pos: &Position) {
-        self.links.remove(pos);
+    /// The cell's bit in `Grid::links` for a passage leading this way.
+    fn bit(self) -> u8 {
+        match self {
+            Direction::North => 0b0001,
+            Direction::South => 0b0010,
+            Direction::East => 0b0100,
+            Direction::West => 0b1000,
+        }
     }
 
-    fn links(&self) -> impl Iterator<Item = &Position> {
-        self.links.iter()
+    /// The direction of `to` as seen from `from`, if the two cells are adjacent.
+    fn between(from: Position, to: Position) -> Option<Direction> {
+        match (to.0 - from.0, to.1 - from.1) {
+            (0, -1) => Some(Direction::North),
+            (0, 1) => Some(Direction::South),
+            (1, 0) => Some(Direction::East),
+            (-1, 0) => Some(Direction::West),
+            _ => None,
+        }
     }
+}
 
-    fn linked(&self, pos: &Position) -> bool {
-        self.links.contains(pos)
-    }
+pub const WALL_WEST: u8 = 0b0001;
+pub const WALL_SOUTH: u8 = 0b0010;
+pub const WALL_EAST: u8 = 0b0100;
+pub const WALL_NORTH: u8 = 0b1000;
 
-    pub fn linked_optional(&self, pos: Option<&Position>) -> bool {
-        if let Some(pos) = pos {
-            self.links.contains(pos)
-        } else {
-            false
-        }
-    }
+/// Why a link between two cells was refused.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GridError {
+    OutOfBounds(Position),
+    NotAdjacent(Position, Position),
+    /// A tunnel can only pass under a straight passage that crosses it and has no tunnel under
+    /// it yet.
+    CannotTunnel(Position),
+}
 
-    fn neighbors(&self) -> Vec<Position> {
-        let mut neighbors = vec![];
-        if let Some(n) = self.north {
-            neighbors.push(n);
-        }
-        if let Some(n) = self.south {
-            neighbors.push(n);
-        }
-        if let Some(n) = self.east {
-            neighbors.push(n);
-        }
-        if let Some(n) = self.west {
-            neighbors.push(n);
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::OutOfBounds(pos) => write!(f, "cell {:?} is outside the maze", pos),
+            GridError::NotAdjacent(from, to) => {
+                write!(f, "cells {:?} and {:?} aren't adjacent", from, to)
+            }
+            GridError::CannotTunnel(pos) => write!(f, "can't tunnel under cell {:?}", pos),
         }
-        neighbors
-    }
-
-    pub fn clear(&mut self) {
-        self.links.clear();
     }
 }
 
+impl Error for GridError {}
+
+/// A rectangular maze. Each cell stores the passages leaving it as one `Direction` bit in a
+/// flat row-major array, so a cell costs a single byte however large the maze is.
 #[derive(PartialEq, Debug)]
 pub struct Grid {
-    cells: Vec<Vec<Cell>>,
+    links: Vec<u8>,
     rows: usize,
     columns: usize,
 }
 
 impl Grid {
+    /// Panics if `rows * columns` overflows a `usize`.
     pub fn new(rows: usize, columns: usize) -> Self {
-        let cells = (0..rows)
-            .into_iter()
-            .map(|y| {
-                (0..columns)
-                    .into_iter()
-                    .map(|x| Cell::new((x as isize, y as isize)))
-                    .collect()
-            })
-            .collect();
-        let mut grid = Grid {
-            cells,
+        let cells = rows
+            .checked_mul(columns)
+            .unwrap_or_else(|| panic!("a {}x{} maze has too many cells", rows, columns));
+        Grid {
+            links: vec![0; cells],
             rows,
             columns,
-        };
-        grid.configure_cells();
-        grid
+        }
     }
 
-    fn get_cell_mut(&mut self, pos: &Position) -> Option<&mut Cell> {
-        let (x, y) = *pos;
-        let row = self.cells.get_mut(y as usize)?;
-        row.get_mut(x as usize)
+    fn index(&self, (x, y): Position) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.columns || y as usize >= self.rows {
+            return None;
+        }
+        Some(y as usize * self.columns + x as usize)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
     }
 
-    fn get_cell(&self, pos: &Position) -> Option<&Cell> {
-        let (x, y) = *pos;
-        let row = self.cells.get(y as usize)?;
-        row.get(x as usize)
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// A read-only view of the cell at `pos`, or `None` outside the grid.
+    pub fn cell(&self, pos: Position) -> Option<CellRef<'_>> {
+        self.index(pos)?;
+        Some(CellRef { grid: self, pos })
     }
 
     pub fn regenerate(&mut self) {
-        for cell in self.each_cell_mut() {
-            cell.clear();
+        for links in self.links.iter_mut() {
+            *links = 0;
         }
     }
 
-    fn configure_cells(&mut self) {
-        let rows = self.rows as isize;
-        let columns = self.columns as isize;
-        for cell in self.each_cell_mut() {
-            let (x, y) = cell.pos;
-            cell.north = if y > 0 {
-                Some((x as isize, (y - 1) as isize))
-            } else {
-                None
-            };
-            cell.south = if y < rows - 1 {
-                Some((x as isize, (y + 1) as isize))
-            } else {
-                None
-            };
-            cell.east = if x < columns - 1 {
-                Some(((x + 1) as isize, y as isize))
-            } else {
-                None
-            };
-            cell.west = if x > 0 {
-                Some(((x - 1) as isize, y as isize))
-            } else {
-                None
-            };
-        }
+    /// Panics if either cell is outside the grid or the cells aren't adjacent; `try_link` reports
+    /// those as errors instead.
+    pub fn link_cell(&mut self, from: Position, to: Position, bidi: bool) {
+        self.try_link(from, to, bidi)
+            .unwrap_or_else(|e| panic!("{}", e));
     }
 
-    fn random_cell(&mut self) -> &mut Cell {
-        let mut rng = rand::thread_rng();
-        let row = self.cells.choose_mut(&mut rng).expect("empty cells");
-        row.choose_mut(&mut rng).expect("empty column")
+    pub fn try_link(&mut self, from: Position, to: Position, bidi: bool) -> Result<(), GridError> {
+        let (from_index, to_index, direction) = self.edge(from, to)?;
+        self.links[from_index] |= direction.bit();
+        if bidi {
+            self.links[to_index] |= direction.opposite().bit();
+        }
+        Ok(())
     }
 
-    fn each_row(&self) -> impl Iterator<Item = &Vec<Cell>> {
-        self.cells.iter()
+    /// Links `from` both ways with its neighbor in `direction`. Does nothing at the border.
+    pub fn link_cell_to(&mut self, from: Position, direction: Direction) {
+        if let Some(to) = self.neighbor_of_cell(from, direction) {
+            self.link_cell(from, to, true);
+        }
     }
 
-    fn each_cell(&self) -> impl Iterator<Item = &Cell> {
-        self.each_row().flat_map(|row| row.iter())
+    pub fn link_cell_to_north(&mut self, from: Position) {
+        self.link_cell_to(from, Direction::North);
     }
 
-    fn each_cell_do(&self, f: impl FnMut(&Cell)) {
-        self.each_row().flat_map(|row| row.iter()).for_each(f)
+    pub fn link_cell_to_south(&mut self, from: Position) {
+        self.link_cell_to(from, Direction::South);
     }
 
-    fn each_cell_mut_do(&mut self, f: impl FnMut(&mut Cell)) {
-        self.each_row_mut()
-            .flat_map(|row| row.iter_mut())
-            .for_each(f)
+    pub fn link_cell_to_east(&mut self, from: Position) {
+        self.link_cell_to(from, Direction::East);
     }
 
-    fn each_row_mut(&mut self) -> impl Iterator<Item = &mut Vec<Cell>> {
-        self.cells.iter_mut()
+    pub fn link_cell_to_west(&mut self, from: Position) {
+        self.link_cell_to(from, Direction::West);
     }
 
-    fn each_cell_mut(&mut self) -> impl Iterator<Item = &mut Cell> {
-        self.each_row_mut().flat_map(|row| row.iter_mut())
+    /// Panics like `link_cell`; see `try_unlink`.
+    pub fn unlink_cell(&mut self, from: Position, to: Position, bidi: bool) {
+        self.try_unlink(from, to, bidi)
+            .unwrap_or_else(|e| panic!("{}", e));
     }
 
-    pub fn link_cell(&mut self, from: Position, to: Position, bidi: bool) {
-        let cell = self.get_cell_mut(&from).unwrap();
-        cell.link(to);
+    pub fn try_unlink(
+        &mut self,
+        from: Position,
+        to: Position,
+        bidi: bool,
+    ) -> Result<(), GridError> {
+        let (from_index, to_index, direction) = self.edge(from, to)?;
+        self.links[from_index] &= !direction.bit();
         if bidi {
-            let cell = self.get_cell_mut(&to).unwrap();
-            cell.link(from);
+            self.links[to_index] &= !direction.opposite().bit();
         }
+        Ok(())
     }
 
-    pub fn link_cell_to_east(&mut self, from: Position) {
-        if let Some(east) = self.east_of_cell(from) {
-            self.link_cell(from, east, true);
+    fn edge(&self, from: Position, to: Position) -> Result<(usize, usize, Direction), GridError> {
+        let from_index = self.index(from).ok_or(GridError::OutOfBounds(from))?;
+        let to_index = self.index(to).ok_or(GridError::OutOfBounds(to))?;
+        let direction = Direction::between(from, to).ok_or(GridError::NotAdjacent(from, to))?;
+        Ok((from_index, to_index, direction))
+    }
+
+    fn is_open(&self, pos: Position, direction: Direction) -> bool {
+        match self.index(pos) {
+            Some(i) => self.links[i] & direction.bit() != 0,
+            None => false,
         }
     }
 
-    pub fn link_cell_to_north(&mut self, from: Position) {
-        if let Some(east) = self.north_of_cell(from) {
-            self.link_cell(from, east, true);
+    pub fn is_linked(&self, from: Position, to: Position) -> bool {
+        match Direction::between(from, to) {
+            Some(direction) => self.is_open(from, direction),
+            None => false,
         }
     }
 
-    pub fn unlink_cell(&mut self, from: Position, to: Position, bidi: bool) {
-        let cell = self.get_cell_mut(&from).unwrap();
-        cell.unlink(&to);
-        if bidi {
-            let cell = self.get_cell_mut(&to).unwrap();
-            cell.unlink(&from);
+    pub fn is_linked_optional(&self, from: Position, to: Option<Position>) -> bool {
+        to.map(|to| self.is_linked(from, to)).unwrap_or(false)
+    }
+
+    /// Neighboring cells linked to `pos`, in north, south, east, west order.
+    pub fn links_of_cell(&self, pos: Position) -> Vec<Position> {
+        Direction::ALL
+            .iter()
+            .filter(|d| self.is_open(pos, **d))
+            .filter_map(|d| self.neighbor_of_cell(pos, *d))
+            .collect()
+    }
+
+    /// Cells in line of sight from `pos`: the cell itself, then each straight corridor leaving
+    /// it followed until a wall, in north, south, east, west order.
+    pub fn visible_from(&self, pos: Position) -> Vec<Position> {
+        if self.index(pos).is_none() {
+            return vec![];
         }
+        let mut visible = vec![pos];
+        for direction in Direction::ALL.iter() {
+            let mut current = pos;
+            while self.is_open(current, *direction) {
+                match self.neighbor_of_cell(current, *direction) {
+                    Some(next) => current = next,
+                    None => break,
+                }
+                visible.push(current);
+            }
+        }
+        visible
+    }
+
+    pub fn neighbor_of_cell(&self, (x, y): Position, direction: Direction) -> Option<Position> {
+        self.index((x, y))?;
+        let neighbor = match direction {
+            Direction::North => (x, y - 1),
+            Direction::South => (x, y + 1),
+            Direction::East => (x + 1, y),
+            Direction::West => (x - 1, y),
+        };
+        self.index(neighbor).map(|_| neighbor)
     }
 
     pub fn north_of_cell(&self, pos: Position) -> Option<Position> {
-        self.get_cell(&pos)?.north
+        self.neighbor_of_cell(pos, Direction::North)
     }
 
     pub fn south_of_cell(&self, pos: Position) -> Option<Position> {
-        self.get_cell(&pos)?.south
+        self.neighbor_of_cell(pos, Direction::South)
     }
 
     pub fn east_of_cell(&self, pos: Position) -> Option<Position> {
-        self.get_cell(&pos)?.east
+        self.neighbor_of_cell(pos, Direction::East)
     }
 
     pub fn west_of_cell(&self, pos: Position) -> Option<Position> {
-        self.get_cell(&pos)?.west
+        self.neighbor_of_cell(pos, Direction::West)
     }
 
     pub fn iter(&self) -> GridIter {
@@ -236,28 +284,96 @@ impl Grid {
     }
 
     pub fn sprite_for_cell(&self, pos: Position) -> Option<u8> {
-        let cell = self.get_cell(&pos)?;
-        let mut sprite: u8 = 0;
-        if cell.west.is_none() {
-            sprite |= 0b0001;
+        self.walls_of_cell(pos)
+    }
+
+    /// The walls to draw for the cell as a `WALL_*` mask. Only the eastern and southern walls
+    /// are included for inner cells; their northern and western walls belong to the neighbors.
+    pub fn walls_of_cell(&self, (x, y): Position) -> Option<u8> {
+        let links = self.links[self.index((x, y))?];
+        let mut walls: u8 = 0;
+        if x == 0 {
+            walls |= WALL_WEST;
         }
-        if cell.east.is_none() {
-            sprite |= 0b0100;
+        if y == 0 {
+            walls |= WALL_NORTH;
         }
-        if cell.north.is_none() {
-            sprite |= 0b1000;
+        if links & Direction::East.bit() == 0 {
+            walls |= WALL_EAST;
         }
-        if cell.south.is_none() {
-            sprite |= 0b0010;
+        if links & Direction::South.bit() == 0 {
+            walls |= WALL_SOUTH;
         }
+        Some(walls)
+    }
 
-        if !cell.linked_optional(cell.east.as_ref()) {
-            sprite |= 0b0100;
+    /// Whether a wall runs along the top edge of `(x, y)`. `y` may equal `rows` for the bottom
+    /// border.
+    pub fn is_wall_north_of(&self, (x, y): Position) -> bool {
+        if y == 0 {
+            self.walls_of_cell((x, 0)).unwrap_or(0) & WALL_NORTH != 0
+        } else {
+            self.walls_of_cell((x, y - 1)).unwrap_or(0) & WALL_SOUTH != 0
         }
-        if !cell.linked_optional(cell.south.as_ref()) {
-            sprite |= 0b0010;
+    }
+
+    /// Whether a wall runs along the left edge of `(x, y)`. `x` may equal `columns` for the
+    /// right border.
+    pub fn is_wall_west_of(&self, (x, y): Position) -> bool {
+        if x == 0 {
+            self.walls_of_cell((0, y)).unwrap_or(0) & WALL_WEST != 0
+        } else {
+            self.walls_of_cell((x - 1, y)).unwrap_or(0) & WALL_EAST != 0
         }
-        Some(sprite)
+    }
+}
+
+/// One cell of a `Grid`, as returned by `Grid::cell`.
+#[derive(Copy, Clone)]
+pub struct CellRef<'a> {
+    grid: &'a Grid,
+    pos: Position,
+}
+
+impl<'a> CellRef<'a> {
+    pub fn pos(&self) -> Position {
+        self.pos
+    }
+
+    /// Whether a passage leaves the cell in `direction`.
+    pub fn is_open(&self, direction: Direction) -> bool {
+        self.grid.is_open(self.pos, direction)
+    }
+
+    /// The directions with a passage, in north, south, east, west order.
+    pub fn open_directions(&self) -> Vec<Direction> {
+        Direction::ALL
+            .iter()
+            .copied()
+            .filter(|d| self.is_open(*d))
+            .collect()
+    }
+
+    pub fn neighbor(&self, direction: Direction) -> Option<Position> {
+        self.grid.neighbor_of_cell(self.pos, direction)
+    }
+
+    /// Adjacent cells whether linked or not, in north, south, east, west order.
+    pub fn neighbors(&self) -> Vec<Position> {
+        Direction::ALL
+            .iter()
+            .filter_map(|d| self.neighbor(*d))
+            .collect()
+    }
+
+    /// Linked neighbors, in north, south, east, west order.
+    pub fn links(&self) -> Vec<Position> {
+        self.grid.links_of_cell(self.pos)
+    }
+
+    /// The number of passages leaving the cell; 1 for a dead end.
+    pub fn degree(&self) -> usize {
+        self.links().len()
     }
 }
 
@@ -340,54 +456,74 @@ impl Iterator for GridIter {
     }
 }
 
+/// `{}` draws the maze with `+---+` ASCII, `{:#}` with box-drawing characters.
 impl fmt::Display for Grid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "+")?;
-        for _ in 0..self.columns {
-            write!(f, "---+")?;
+        if f.alternate() {
+            return write_box_drawing(f, self);
         }
-        write!(f, "\n")?;
-
-        for row in self.each_row() {
-            let mut top = "|".to_string();
-            let mut bottom = "+".to_string();
-
-            for cell in row.iter() {
-                let east_boundary = if cell.linked_optional(cell.east.as_ref()) {
-                    " "
-                } else {
-                    "|"
-                };
-                top.push_str("   ");
-                top.push_str(east_boundary);
-
-                let south_boundary = if cell.linked_optional(cell.south.as_ref()) {
-                    "   "
-                } else {
-                    "---"
-                };
-                bottom.push_str(south_boundary);
-                bottom.push_str("+");
-            }
+        write_ascii(
+            f,
+            self.rows,
+            self.columns,
+            |pos| self.walls_of_cell(pos).unwrap_or(0) & WALL_EAST == 0,
+            |pos| self.walls_of_cell(pos).unwrap_or(0) & WALL_SOUTH == 0,
+            |_| "   ",
+        )
+    }
+}
 
-            writeln!(f, "{}", top)?;
-            writeln!(f, "{}", bottom)?;
+/// Writes the `+---+` layout shared by the grids' `Display` impls. `body` returns the three
+/// characters drawn inside each cell.
+pub(crate) fn write_ascii(
+    f: &mut dyn fmt::Write,
+    rows: usize,
+    columns: usize,
+    open_east: impl Fn(Position) -> bool,
+    open_south: impl Fn(Position) -> bool,
+    body: impl Fn(Position) -> &'static str,
+) -> fmt::Result {
+    write!(f, "+")?;
+    for _ in 0..columns {
+        write!(f, "---+")?;
+    }
+    writeln!(f)?;
+
+    for row in (GridRowsIter {
+        rows: rows as isize,
+        columns: columns as isize,
+        y: 0,
+    }) {
+        let mut top = "|".to_string();
+        let mut bottom = "+".to_string();
+
+        for pos in row {
+            let east_boundary = if open_east(pos) { " " } else { "|" };
+            top.push_str(body(pos));
+            top.push_str(east_boundary);
+
+            let south_boundary = if open_south(pos) { "   " } else { "---" };
+            bottom.push_str(south_boundary);
+            bottom.push('+');
         }
 
-        Ok(())
+        writeln!(f, "{}", top)?;
+        writeln!(f, "{}", bottom)?;
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::grid::{Grid, GridIter, GridRow, GridRowsIter};
+    use crate::grid::{Direction, Grid, GridError, GridIter, GridRow, GridRowsIter};
     use crate::test_utils::{check, check_debug};
     use expect_test::expect;
 
     #[test]
     fn test_grid_iter() {
         let iter = GridIter::new(10, 8);
-        let ret = iter.into_iter().collect::<Vec<_>>();
+        let ret = iter.collect::<Vec<_>>();
         check_debug(
             ret,
             expect![[r#"
@@ -724,7 +860,6 @@ mod tests {
             columns: 8,
             y: 0,
         }
-        .into_iter()
         .collect::<Vec<_>>();
         check_debug(
             rows,
@@ -791,7 +926,6 @@ mod tests {
             columns: 5,
             x: 0,
         }
-        .into_iter()
         .collect::<Vec<_>>();
         check_debug(
             poss,
@@ -884,17 +1018,17 @@ mod tests {
     #[test]
     fn test_north_east_south_west() {
         let grid = Grid::new(10, 10);
-        assert_eq!(grid.get_cell(&(0, 0)).unwrap().north, None);
-        assert_eq!(grid.get_cell(&(1, 0)).unwrap().north, None);
-        assert_eq!(grid.get_cell(&(1, 1)).unwrap().north, Some((1, 0)));
-        assert_eq!(grid.get_cell(&(2, 3)).unwrap().north, Some((2, 2)));
-        assert_eq!(grid.get_cell(&(2, 3)).unwrap().east, Some((3, 3)));
-        assert_eq!(grid.get_cell(&(9, 3)).unwrap().east, None);
-        assert_eq!(grid.get_cell(&(0, 3)).unwrap().west, None);
-        assert_eq!(grid.get_cell(&(9, 9)).unwrap().east, None);
-        assert_eq!(grid.get_cell(&(9, 9)).unwrap().north, Some((9, 8)));
-        assert_eq!(grid.get_cell(&(9, 9)).unwrap().east, None);
-        assert_eq!(grid.get_cell(&(9, 9)).unwrap().west, Some((8, 9)));
+        assert_eq!(grid.north_of_cell((0, 0)), None);
+        assert_eq!(grid.north_of_cell((1, 0)), None);
+        assert_eq!(grid.north_of_cell((1, 1)), Some((1, 0)));
+        assert_eq!(grid.north_of_cell((2, 3)), Some((2, 2)));
+        assert_eq!(grid.east_of_cell((2, 3)), Some((3, 3)));
+        assert_eq!(grid.east_of_cell((9, 3)), None);
+        assert_eq!(grid.west_of_cell((0, 3)), None);
+        assert_eq!(grid.east_of_cell((9, 9)), None);
+        assert_eq!(grid.north_of_cell((9, 9)), Some((9, 8)));
+        assert_eq!(grid.east_of_cell((9, 9)), None);
+        assert_eq!(grid.west_of_cell((9, 9)), Some((8, 9)));
     }
 
     #[test]
@@ -904,4 +1038,113 @@ mod tests {
         assert_eq!(grid.sprite_for_cell((0, 1)), Some(0b0111));
         assert_eq!(grid.sprite_for_cell((1, 1)), Some(0b0110));
     }
+
+    #[test]
+    fn test_link_and_unlink() {
+        let mut grid = Grid::new(2, 2);
+        grid.link_cell((0, 0), (1, 0), true);
+        grid.link_cell((1, 1), (1, 0), false);
+        assert_eq!(grid.links_of_cell((1, 0)), vec![(0, 0)]);
+        assert_eq!(grid.links_of_cell((1, 1)), vec![(1, 0)]);
+        assert!(!grid.is_linked((0, 0), (1, 1)));
+        assert!(!grid.is_linked((0, 0), (-1, 0)));
+
+        grid.unlink_cell((1, 0), (0, 0), true);
+        assert!(!grid.is_linked((0, 0), (1, 0)));
+        assert_eq!(grid.links_of_cell((0, 0)), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many cells")]
+    fn test_new_too_large() {
+        Grid::new(usize::MAX, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't adjacent")]
+    fn test_link_non_adjacent() {
+        Grid::new(3, 3).link_cell((0, 0), (2, 0), true);
+    }
+
+    #[test]
+    fn test_cell_ref() {
+        let mut grid = Grid::new(2, 3);
+        grid.link_cell_to_east((0, 0));
+        grid.link_cell_to_south((1, 0));
+        grid.link_cell_to_west((1, 1));
+        grid.link_cell_to_north((2, 1));
+
+        let cell = grid.cell((1, 0)).unwrap();
+        assert_eq!(cell.pos(), (1, 0));
+        assert!(cell.is_open(Direction::West));
+        assert!(cell.is_open(Direction::South));
+        assert!(!cell.is_open(Direction::East));
+        assert!(!cell.is_open(Direction::North));
+        assert_eq!(
+            cell.open_directions(),
+            vec![Direction::South, Direction::West]
+        );
+        assert_eq!(cell.neighbor(Direction::North), None);
+        assert_eq!(cell.neighbors(), vec![(1, 1), (2, 0), (0, 0)]);
+        assert_eq!(cell.links(), vec![(1, 1), (0, 0)]);
+        assert_eq!(cell.degree(), 2);
+
+        assert_eq!(grid.cell((2, 0)).unwrap().degree(), 1);
+        assert!(grid.cell((3, 0)).is_none());
+        assert!(grid.cell((0, -1)).is_none());
+    }
+
+    #[test]
+    fn test_try_link() {
+        let mut grid = Grid::new(2, 3);
+        assert_eq!(grid.try_link((0, 0), (0, 1), true), Ok(()));
+        assert_eq!(
+            grid.try_link((2, 1), (3, 1), true),
+            Err(GridError::OutOfBounds((3, 1)))
+        );
+        assert_eq!(
+            grid.try_link((-1, 0), (0, 0), true),
+            Err(GridError::OutOfBounds((-1, 0)))
+        );
+        assert_eq!(
+            grid.try_link((0, 0), (1, 1), true),
+            Err(GridError::NotAdjacent((0, 0), (1, 1)))
+        );
+        assert_eq!(
+            grid.try_unlink((0, 0), (0, 0), true),
+            Err(GridError::NotAdjacent((0, 0), (0, 0)))
+        );
+        assert_eq!(
+            grid.try_unlink((0, 0), (0, 2), true)
+                .unwrap_err()
+                .to_string(),
+            "cell (0, 2) is outside the maze"
+        );
+        assert_eq!(grid.links_of_cell((0, 0)), vec![(0, 1)]);
+        assert_eq!(grid.try_unlink((0, 1), (0, 0), true), Ok(()));
+        assert_eq!(grid.links_of_cell((0, 0)), vec![]);
+    }
+
+    #[test]
+    fn test_visible_from() {
+        let mut grid = Grid::new(3, 4);
+        grid.link_cell((0, 1), (1, 1), true);
+        grid.link_cell((1, 1), (2, 1), true);
+        grid.link_cell((2, 1), (3, 1), true);
+        grid.link_cell((1, 0), (1, 1), true);
+        grid.link_cell((1, 1), (1, 2), true);
+        grid.link_cell((3, 1), (3, 2), true);
+
+        assert_eq!(
+            grid.visible_from((1, 1)),
+            vec![(1, 1), (1, 0), (1, 2), (2, 1), (3, 1), (0, 1)]
+        );
+        // The corner at (3, 1) blocks the view down to (3, 2).
+        assert_eq!(
+            grid.visible_from((0, 1)),
+            vec![(0, 1), (1, 1), (2, 1), (3, 1)]
+        );
+        assert_eq!(grid.visible_from((0, 0)), vec![(0, 0)]);
+        assert_eq!(grid.visible_from((4, 0)), vec![]);
+    }
 }
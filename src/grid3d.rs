@@ -0,0 +1,280 @@
+use crate::grid::{write_ascii, Grid, WALL_EAST, WALL_SOUTH};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+/// A cell position within a `Grid3D`: `(x, y, level)`.
+pub type Position3D = (isize, isize, isize);
+
+/// Why a link between two cells of a `Grid3D` was refused.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Grid3DError {
+    OutOfBounds(Position3D),
+    NotAdjacent(Position3D, Position3D),
+}
+
+impl fmt::Display for Grid3DError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Grid3DError::OutOfBounds(pos) => write!(f, "cell {:?} is outside the maze", pos),
+            Grid3DError::NotAdjacent(from, to) => {
+                write!(f, "cells {:?} and {:?} aren't adjacent", from, to)
+            }
+        }
+    }
+}
+
+impl Error for Grid3DError {}
+
+/// A stack of equally sized `Grid` levels. Besides the four compass neighbors each cell has an
+/// `up` and a `down` neighbor at the same `(x, y)` on the adjacent levels.
+#[derive(PartialEq, Debug)]
+pub struct Grid3D {
+    levels: Vec<Grid>,
+    rows: usize,
+    columns: usize,
+    /// Cells linked to the cell directly above them.
+    stairs: HashSet<Position3D>,
+}
+
+impl Grid3D {
+    pub fn new(levels: usize, rows: usize, columns: usize) -> Self {
+        Grid3D {
+            levels: (0..levels).map(|_| Grid::new(rows, columns)).collect(),
+            rows,
+            columns,
+            stairs: HashSet::new(),
+        }
+    }
+
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn level(&self, level: isize) -> Option<&Grid> {
+        if level < 0 {
+            return None;
+        }
+        self.levels.get(level as usize)
+    }
+
+    pub fn regenerate(&mut self) {
+        for level in self.levels.iter_mut() {
+            level.regenerate();
+        }
+        self.stairs.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Position3D> + '_ {
+        self.levels
+            .iter()
+            .enumerate()
+            .flat_map(|(z, level)| level.iter().map(move |(x, y)| (x, y, z as isize)))
+    }
+
+    pub fn north_of_cell(&self, (x, y, z): Position3D) -> Option<Position3D> {
+        let (x, y) = self.level(z)?.north_of_cell((x, y))?;
+        Some((x, y, z))
+    }
+
+    pub fn south_of_cell(&self, (x, y, z): Position3D) -> Option<Position3D> {
+        let (x, y) = self.level(z)?.south_of_cell((x, y))?;
+        Some((x, y, z))
+    }
+
+    pub fn east_of_cell(&self, (x, y, z): Position3D) -> Option<Position3D> {
+        let (x, y) = self.level(z)?.east_of_cell((x, y))?;
+        Some((x, y, z))
+    }
+
+    pub fn west_of_cell(&self, (x, y, z): Position3D) -> Option<Position3D> {
+        let (x, y) = self.level(z)?.west_of_cell((x, y))?;
+        Some((x, y, z))
+    }
+
+    pub fn up_of_cell(&self, (x, y, z): Position3D) -> Option<Position3D> {
+        self.level(z)?;
+        self.level(z + 1)?;
+        Some((x, y, z + 1))
+    }
+
+    pub fn down_of_cell(&self, (x, y, z): Position3D) -> Option<Position3D> {
+        self.level(z)?;
+        self.level(z - 1)?;
+        Some((x, y, z - 1))
+    }
+
+    pub fn neighbors_of_cell(&self, pos: Position3D) -> Vec<Position3D> {
+        vec![
+            self.north_of_cell(pos),
+            self.south_of_cell(pos),
+            self.east_of_cell(pos),
+            self.west_of_cell(pos),
+            self.up_of_cell(pos),
+            self.down_of_cell(pos),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Links two neighboring cells in both directions, either within a level or by a stair
+    /// between levels.
+    ///
+    /// Panics if either cell is outside the grid or the cells aren't neighbors; `try_link`
+    /// reports those as errors instead.
+    pub fn link_cell(&mut self, from: Position3D, to: Position3D) {
+        self.try_link(from, to).unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    pub fn try_link(&mut self, from: Position3D, to: Position3D) -> Result<(), Grid3DError> {
+        for &(x, y, z) in [from, to].iter() {
+            if self.level(z).and_then(|level| level.cell((x, y))).is_none() {
+                return Err(Grid3DError::OutOfBounds((x, y, z)));
+            }
+        }
+        let (fx, fy, fz) = from;
+        let (tx, ty, tz) = to;
+        if fz == tz {
+            // Both cells are on the level, so only their adjacency can be wrong.
+            self.levels[fz as usize]
+                .try_link((fx, fy), (tx, ty), true)
+                .map_err(|_| Grid3DError::NotAdjacent(from, to))
+        } else if (fx, fy) == (tx, ty) && (fz - tz).abs() == 1 {
+            self.stairs.insert(if fz < tz { from } else { to });
+            Ok(())
+        } else {
+            Err(Grid3DError::NotAdjacent(from, to))
+        }
+    }
+
+    pub fn is_linked(&self, from: Position3D, to: Position3D) -> bool {
+        let (fx, fy, fz) = from;
+        let (tx, ty, tz) = to;
+        if fz == tz {
+            self.level(fz)
+                .map(|level| level.is_linked((fx, fy), (tx, ty)))
+                .unwrap_or(false)
+        } else if (fx, fy) == (tx, ty) && (fz - tz).abs() == 1 {
+            self.stairs.contains(if fz < tz { &from } else { &to })
+        } else {
+            false
+        }
+    }
+
+    pub fn is_linked_up(&self, pos: Position3D) -> bool {
+        self.stairs.contains(&pos)
+    }
+
+    pub fn is_linked_down(&self, (x, y, z): Position3D) -> bool {
+        self.stairs.contains(&(x, y, z - 1))
+    }
+}
+
+/// Levels are drawn side by side, lowest first. A stair up is marked `U`, a stair down `D`.
+impl fmt::Display for Grid3D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut levels = vec![];
+        for (z, level) in self.levels.iter().enumerate() {
+            let z = z as isize;
+            let mut out = String::new();
+            write_ascii(
+                &mut out,
+                self.rows,
+                self.columns,
+                |pos| level.walls_of_cell(pos).unwrap_or(0) & WALL_EAST == 0,
+                |pos| level.walls_of_cell(pos).unwrap_or(0) & WALL_SOUTH == 0,
+                |(x, y)| match (self.is_linked_up((x, y, z)), self.is_linked_down((x, y, z))) {
+                    (true, true) => "U D",
+                    (true, false) => " U ",
+                    (false, true) => " D ",
+                    (false, false) => "   ",
+                },
+            )?;
+            levels.push(out);
+        }
+
+        let mut lines = levels.iter().map(|level| level.lines()).collect::<Vec<_>>();
+        for _ in 0..self.rows * 2 + 1 {
+            let row = lines
+                .iter_mut()
+                .filter_map(|level| level.next())
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", row.join("   "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid3d::{Grid3D, Grid3DError};
+    use crate::test_utils::check;
+    use expect_test::expect;
+
+    #[test]
+    fn test_up_down() {
+        let grid = Grid3D::new(2, 3, 3);
+        assert_eq!(grid.up_of_cell((0, 0, 0)), Some((0, 0, 1)));
+        assert_eq!(grid.up_of_cell((0, 0, 1)), None);
+        assert_eq!(grid.down_of_cell((2, 1, 1)), Some((2, 1, 0)));
+        assert_eq!(grid.down_of_cell((2, 1, 0)), None);
+        assert_eq!(grid.neighbors_of_cell((0, 0, 1)).len(), 3);
+    }
+
+    #[test]
+    fn test_display_grid3d() {
+        let mut grid = Grid3D::new(2, 2, 3);
+        grid.link_cell((0, 0, 0), (1, 0, 0));
+        grid.link_cell((1, 0, 0), (1, 0, 1));
+        grid.link_cell((1, 0, 1), (1, 1, 1));
+        assert!(grid.is_linked((1, 0, 1), (1, 0, 0)));
+        assert!(!grid.is_linked((0, 0, 0), (0, 0, 1)));
+        check(
+            &grid,
+            expect![[r#"
+                +---+---+---+   +---+---+---+
+                |     U |   |   |   | D |   |
+                +---+---+---+   +---+   +---+
+                |   |   |   |   |   |   |   |
+                +---+---+---+   +---+---+---+
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_try_link() {
+        let mut grid = Grid3D::new(2, 2, 2);
+        assert_eq!(
+            grid.try_link((0, 0, 1), (0, 0, 2)),
+            Err(Grid3DError::OutOfBounds((0, 0, 2)))
+        );
+        assert_eq!(
+            grid.try_link((0, 0, -1), (1, 0, -1)),
+            Err(Grid3DError::OutOfBounds((0, 0, -1)))
+        );
+        assert_eq!(
+            grid.try_link((0, 2, 0), (0, 1, 0)),
+            Err(Grid3DError::OutOfBounds((0, 2, 0)))
+        );
+        assert_eq!(
+            grid.try_link((0, 0, 0), (1, 1, 0)),
+            Err(Grid3DError::NotAdjacent((0, 0, 0), (1, 1, 0)))
+        );
+        assert_eq!(
+            grid.try_link((0, 0, 0), (1, 0, 1)),
+            Err(Grid3DError::NotAdjacent((0, 0, 0), (1, 0, 1)))
+        );
+        assert_eq!(grid, Grid3D::new(2, 2, 2));
+        assert_eq!(grid.try_link((1, 1, 1), (1, 1, 0)), Ok(()));
+        assert!(grid.is_linked_up((1, 1, 0)));
+    }
+}
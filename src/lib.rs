@@ -0,0 +1,24 @@
+//! Maze grids, generators and exporters. The Bevy game in `src/bin/maze` is built on top of
+//! this library and needs the `game` feature, and `maze-cli` needs the `cli` feature. Neither is
+//! on by default, so depending on the library leaves the game engine out; run the game with
+//! `cargo run --features game --bin maze`.
+
+#[cfg(test)]
+#[macro_use]
+mod test_utils;
+pub mod algorithms;
+pub mod binary_tree;
+pub mod distances;
+pub mod grid;
+pub mod grid3d;
+pub mod maze_code;
+pub mod parse;
+pub mod random;
+pub mod raster;
+pub mod recursive_backtracker;
+pub mod serialize;
+pub mod sidewinder;
+pub mod svg;
+pub mod text;
+pub mod walls;
+pub mod weave_grid;
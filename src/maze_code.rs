@@ -0,0 +1,315 @@
+use crate::algorithms::Algorithm;
+use crate::grid::{Grid, GridError};
+use rand_pcg::Pcg32;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Crockford's base32 alphabet, which leaves out the easily confused I, L, O and U.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const VERSION: u8 = 0;
+/// The PCG stream every maze code is generated with.
+const STREAM: u64 = 0x6d61_7a65;
+
+/// Everything needed to regenerate a maze, written as a short base32 string that players can
+/// share.
+///
+/// Codes are generated with PCG32, whose output is fixed by its specification, and the
+/// generators turn its numbers into choices with `random` rather than `rand`'s own methods, so
+/// a code keeps producing the same maze as long as the algorithms don't change.
+/// `test_maze_code_is_stable` catches any that do.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MazeCode {
+    pub algorithm: Algorithm,
+    pub rows: usize,
+    pub columns: usize,
+    pub seed: u64,
+}
+
+impl MazeCode {
+    pub fn rng(&self) -> Pcg32 {
+        Pcg32::new(self.seed, STREAM)
+    }
+
+    pub fn generate(&self) -> Result<Grid, GridError> {
+        let mut grid = Grid::new(self.rows, self.columns);
+        self.algorithm.generate(&mut grid, &mut self.rng())?;
+        Ok(grid)
+    }
+}
+
+/// Why a string isn't a valid `MazeCode`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseMazeCodeError {
+    InvalidCharacter(char),
+    Truncated,
+    TrailingData,
+    UnsupportedVersion(u8),
+    UnknownAlgorithm(u8),
+    /// A number doesn't fit in 64 bits.
+    Overflow,
+    /// The code has more bytes or bits than needed, so the same maze would have several codes.
+    NotCanonical,
+}
+
+impl fmt::Display for ParseMazeCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseMazeCodeError::InvalidCharacter(c) => write!(f, "invalid character `{}`", c),
+            ParseMazeCodeError::Truncated => write!(f, "maze code is too short"),
+            ParseMazeCodeError::TrailingData => write!(f, "maze code is too long"),
+            ParseMazeCodeError::UnsupportedVersion(v) => {
+                write!(f, "unsupported maze code version {}", v)
+            }
+            ParseMazeCodeError::UnknownAlgorithm(id) => write!(f, "unknown algorithm id {}", id),
+            ParseMazeCodeError::Overflow => write!(f, "maze code holds a number that's too large"),
+            ParseMazeCodeError::NotCanonical => write!(f, "maze code isn't in its shortest form"),
+        }
+    }
+}
+
+impl Error for ParseMazeCodeError {}
+
+/// Version and algorithm id in one byte, then rows, columns and seed as LEB128 varints.
+impl fmt::Display for MazeCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = vec![VERSION << 4 | self.algorithm.id()];
+        write_varint(&mut bytes, self.rows as u64);
+        write_varint(&mut bytes, self.columns as u64);
+        write_varint(&mut bytes, self.seed);
+        f.write_str(&encode_base32(&bytes))
+    }
+}
+
+/// Accepts lower case and ignores `-` separators.
+impl FromStr for MazeCode {
+    type Err = ParseMazeCodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = decode_base32(s)?;
+        let (&header, mut rest) = bytes.split_first().ok_or(ParseMazeCodeError::Truncated)?;
+        if header >> 4 != VERSION {
+            return Err(ParseMazeCodeError::UnsupportedVersion(header >> 4));
+        }
+        let algorithm = Algorithm::from_id(header & 0x0f)
+            .ok_or(ParseMazeCodeError::UnknownAlgorithm(header & 0x0f))?;
+        let rows = read_varint(&mut rest)? as usize;
+        let columns = read_varint(&mut rest)? as usize;
+        let seed = read_varint(&mut rest)?;
+        if !rest.is_empty() {
+            return Err(ParseMazeCodeError::TrailingData);
+        }
+        Ok(MazeCode {
+            algorithm,
+            rows,
+            columns,
+            seed,
+        })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Only accepts what `write_varint` writes: no more than 64 bits, and no high zero bytes.
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, ParseMazeCodeError> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = bytes.split_first().ok_or(ParseMazeCodeError::Truncated)?;
+        *bytes = rest;
+        let bits = u64::from(byte & 0x7f);
+        if shift >= 64 || bits << shift >> shift != bits {
+            return Err(ParseMazeCodeError::Overflow);
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            if byte == 0 && shift > 0 {
+                return Err(ParseMazeCodeError::NotCanonical);
+            }
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn encode_base32(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = buffer << 8 | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    out
+}
+
+fn decode_base32(s: &str) -> Result<Vec<u8>, ParseMazeCodeError> {
+    let mut bytes = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in s.chars().filter(|c| *c != '-') {
+        let value = match c.to_ascii_uppercase() {
+            'O' => 0,
+            'I' | 'L' => 1,
+            upper => ALPHABET
+                .iter()
+                .position(|a| *a as char == upper)
+                .ok_or(ParseMazeCodeError::InvalidCharacter(c))? as u32,
+        };
+        buffer = (buffer << 5 | value) & 0xffff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    // `encode_base32` pads the last byte with fewer than five zero bits.
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return Err(ParseMazeCodeError::NotCanonical);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::Algorithm;
+    use crate::maze_code::{encode_base32, MazeCode, ParseMazeCodeError};
+    use crate::test_utils::check;
+    use expect_test::expect;
+
+    const DAILY: MazeCode = MazeCode {
+        algorithm: Algorithm::Sidewinder,
+        rows: 6,
+        columns: 8,
+        seed: 20261018,
+    };
+
+    #[test]
+    fn test_maze_code_round_trip() {
+        let code = DAILY.to_string();
+        check(&code, expect![[r#"0430H6PHTG4G"#]]);
+        assert_eq!(code.parse::<MazeCode>(), Ok(DAILY));
+        assert_eq!(code.to_lowercase().parse::<MazeCode>(), Ok(DAILY));
+
+        let huge = MazeCode {
+            algorithm: Algorithm::RecursiveBacktracker,
+            rows: 2000,
+            columns: 2000,
+            seed: u64::MAX,
+        };
+        assert_eq!(huge.to_string().parse::<MazeCode>(), Ok(huge));
+    }
+
+    #[test]
+    fn test_maze_code_errors() {
+        assert_eq!("".parse::<MazeCode>(), Err(ParseMazeCodeError::Truncated));
+        assert_eq!(
+            "0U".parse::<MazeCode>(),
+            Err(ParseMazeCodeError::InvalidCharacter('U'))
+        );
+        assert_eq!(
+            "Z0".parse::<MazeCode>(),
+            Err(ParseMazeCodeError::UnsupportedVersion(15))
+        );
+        assert_eq!(
+            "1W".parse::<MazeCode>(),
+            Err(ParseMazeCodeError::UnknownAlgorithm(15))
+        );
+        let code = DAILY.to_string();
+        assert_eq!(
+            code[..code.len() - 2].parse::<MazeCode>(),
+            Err(ParseMazeCodeError::Truncated)
+        );
+        assert_eq!(
+            parse_bytes(&[0x00, 0x02, 0x02, 0x03, 0x00]),
+            Err(ParseMazeCodeError::TrailingData)
+        );
+    }
+
+    fn parse_bytes(bytes: &[u8]) -> Result<MazeCode, ParseMazeCodeError> {
+        encode_base32(bytes).parse()
+    }
+
+    #[test]
+    fn test_numbers_too_large() {
+        // The tenth byte of a seed only has room for bit 63.
+        let mut bytes = vec![0x00, 0x02, 0x02];
+        bytes.extend(&[0xff; 9]);
+        assert_eq!(
+            parse_bytes(&[&bytes[..], &[0x01]].concat()).unwrap().seed,
+            u64::MAX
+        );
+        assert_eq!(
+            parse_bytes(&[&bytes[..], &[0x02]].concat()),
+            Err(ParseMazeCodeError::Overflow)
+        );
+        // Eleven bytes shift past 64 bits even when they are all zero.
+        let mut bytes = vec![0x00, 0x02, 0x02];
+        bytes.extend(&[0x80; 10]);
+        bytes.push(0x00);
+        assert_eq!(parse_bytes(&bytes), Err(ParseMazeCodeError::Overflow));
+    }
+
+    #[test]
+    fn test_numbers_not_minimal() {
+        assert_eq!(parse_bytes(&[0x00, 0x02, 0x02, 0x03]).unwrap().seed, 3);
+        assert_eq!(
+            parse_bytes(&[0x00, 0x02, 0x02, 0x83, 0x00]),
+            Err(ParseMazeCodeError::NotCanonical)
+        );
+        assert_eq!(
+            parse_bytes(&[0x00, 0x82, 0x00, 0x02, 0x03]),
+            Err(ParseMazeCodeError::NotCanonical)
+        );
+    }
+
+    #[test]
+    fn test_padding_bits() {
+        // One byte takes two characters, with two bits left over that must be zero.
+        assert_eq!(
+            "1Z".parse::<MazeCode>(),
+            Err(ParseMazeCodeError::NotCanonical)
+        );
+        // A whole character left over holds no byte at all.
+        assert_eq!(
+            format!("{}00", DAILY).parse::<MazeCode>(),
+            Err(ParseMazeCodeError::NotCanonical)
+        );
+    }
+
+    /// Guards the promise that a shared code always produces the same maze.
+    #[test]
+    fn test_maze_code_is_stable() {
+        check(
+            &DAILY.generate().unwrap(),
+            expect![[r#"
+                +---+---+---+---+---+---+---+---+
+                |                               |
+                +   +---+   +---+---+---+---+---+
+                |   |                           |
+                +   +   +---+---+   +---+   +   +
+                |   |           |       |   |   |
+                +   +---+---+---+---+   +---+   +
+                |   |                   |       |
+                +   +---+   +---+---+   +---+---+
+                |   |               |           |
+                +---+   +   +   +   +   +   +---+
+                |       |   |   |   |   |       |
+                +---+---+---+---+---+---+---+---+
+            "#]],
+        );
+    }
+}
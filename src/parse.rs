@@ -0,0 +1,205 @@
+use crate::grid::Grid;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Why a maze couldn't be read back from its `+---+` text. Lines and columns start at 1.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseGridError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseGridError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseGridError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseGridError {}
+
+/// Reads the exact layout written by `impl Display for Grid`.
+impl FromStr for Grid {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s
+            .lines()
+            .map(|l| l.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let top = lines
+            .first()
+            .ok_or_else(|| ParseGridError::new(1, 1, "empty maze"))?;
+        if top.len() < 5 || top.len() % 4 != 1 {
+            return Err(ParseGridError::new(
+                1,
+                top.len() + 1,
+                "the top border must be `+` followed by `---+` for every column",
+            ));
+        }
+        let columns = top.len() / 4;
+        if lines.len() < 3 || lines.len() % 2 != 1 {
+            return Err(ParseGridError::new(
+                lines.len() + 1,
+                1,
+                "expected a line of cells followed by a border line for every row",
+            ));
+        }
+        let rows = lines.len() / 2;
+
+        let mut grid = Grid::new(rows, columns);
+        for (i, line) in lines.iter().enumerate() {
+            let line_number = i + 1;
+            if line.len() != columns * 4 + 1 {
+                return Err(ParseGridError::new(
+                    line_number,
+                    line.len().min(columns * 4 + 1) + 1,
+                    format!(
+                        "line is {} characters long, expected {}",
+                        line.len(),
+                        columns * 4 + 1
+                    ),
+                ));
+            }
+            let y = (i / 2) as isize;
+            let is_border = i % 2 == 0;
+            let is_outer = i == 0 || i == lines.len() - 1;
+
+            for x in 0..columns {
+                let start = x * 4;
+                let expect = |offset: usize, allowed: &str| {
+                    let c = line[start + offset];
+                    if allowed.contains(c) {
+                        Ok(c)
+                    } else {
+                        let allowed = allowed
+                            .chars()
+                            .map(|a| format!("`{}`", a))
+                            .collect::<Vec<_>>()
+                            .join(" or ");
+                        Err(ParseGridError::new(
+                            line_number,
+                            start + offset + 1,
+                            format!("expected {}, found `{}`", allowed, c),
+                        ))
+                    }
+                };
+
+                if is_border {
+                    expect(0, "+")?;
+                    let segment = if is_outer { "-" } else { "- " };
+                    let first = expect(1, segment)?;
+                    expect(2, &first.to_string())?;
+                    expect(3, &first.to_string())?;
+                    if first == ' ' {
+                        grid.link_cell((x as isize, y - 1), (x as isize, y), true);
+                    }
+                } else {
+                    expect(0, if x == 0 { "|" } else { "| " })?;
+                    for offset in 1..4 {
+                        expect(offset, " ")?;
+                    }
+                    if x > 0 && line[start] == ' ' {
+                        grid.link_cell((x as isize - 1, y), (x as isize, y), true);
+                    }
+                }
+            }
+
+            let last = *line.last().unwrap();
+            let expected = if is_border { '+' } else { '|' };
+            if last != expected {
+                return Err(ParseGridError::new(
+                    line_number,
+                    line.len(),
+                    format!("expected `{}`, found `{}`", expected, last),
+                ));
+            }
+        }
+
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+    use crate::parse::ParseGridError;
+
+    fn parse_err(s: &str) -> String {
+        s.parse::<Grid>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_parse_grid() {
+        let text = "\
++---+---+---+
+|       |   |
++---+   +   +
+|           |
++---+---+---+
+";
+        let grid = text.parse::<Grid>().unwrap();
+        assert_eq!(grid.rows(), 2);
+        assert_eq!(grid.columns(), 3);
+        assert!(grid.is_linked((0, 0), (1, 0)));
+        assert!(grid.is_linked((1, 0), (1, 1)));
+        assert!(!grid.is_linked((1, 0), (2, 0)));
+        assert_eq!(grid.to_string(), text);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_err(""), "line 1, column 1: empty maze");
+        assert_eq!(
+            parse_err("+---+--+\n|   |\n+---+\n"),
+            "line 1, column 9: the top border must be `+` followed by `---+` for every column"
+        );
+        assert_eq!(
+            parse_err("+---+\n|   |\n"),
+            "line 3, column 1: expected a line of cells followed by a border line for every row"
+        );
+        assert_eq!(
+            parse_err("+---+---+\n|   x   |\n+---+---+\n"),
+            "line 2, column 5: expected `|` or ` `, found `x`"
+        );
+        assert_eq!(
+            parse_err("+---+---+\n|       |\n+--- ---+\n"),
+            "line 3, column 5: expected `+`, found ` `"
+        );
+        assert_eq!(
+            parse_err("+---+---+\n|       |\n+---+   +\n"),
+            "line 3, column 6: expected `-`, found ` `"
+        );
+        assert_eq!(
+            parse_err("+---+\n|    \n+---+\n"),
+            "line 2, column 5: expected `|`, found ` `"
+        );
+        assert_eq!(
+            parse_err("+---+\n|   |\n+---+---+\n"),
+            "line 3, column 6: line is 9 characters long, expected 5"
+        );
+        assert_eq!(
+            "+---+\n|   |\n+- -+\n".parse::<Grid>(),
+            Err(ParseGridError {
+                line: 3,
+                column: 3,
+                message: "expected `-`, found ` `".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Random choices for the generators, made from `RngCore::next_u32` alone.
+//!
+//! `rand`'s own `gen_range`, `gen::<bool>` and `SliceRandom::choose` may turn random numbers
+//! into choices differently from one release to the next, which would change the maze behind
+//! every maze code. These only depend on the numbers the generator returns.
+
+use rand::RngCore;
+
+/// A number in `0..n`, by Lemire's multiply-and-shift, drawing again in the rare case that
+/// would favour some numbers over others.
+///
+/// Panics if `n` is zero or doesn't fit in a `u32`.
+pub fn below<R: RngCore + ?Sized>(rng: &mut R, n: usize) -> usize {
+    assert!(n > 0, "can't choose a number below zero");
+    assert!(n <= u32::MAX as usize, "can't choose a number below {}", n);
+    let n = n as u32;
+    // 2^32 mod n: products whose low half is below this are the surplus that would bias the
+    // result.
+    let surplus = n.wrapping_neg() % n;
+    loop {
+        let product = u64::from(rng.next_u32()) * u64::from(n);
+        if product as u32 >= surplus {
+            return (product >> 32) as usize;
+        }
+    }
+}
+
+/// A fair coin toss: the top bit of the next number.
+pub fn coin<R: RngCore + ?Sized>(rng: &mut R) -> bool {
+    rng.next_u32() >> 31 == 1
+}
+
+/// A random element of `items`, or `None` if it is empty.
+pub fn choose<'a, T, R: RngCore + ?Sized>(rng: &mut R, items: &'a [T]) -> Option<&'a T> {
+    if items.is_empty() {
+        None
+    } else {
+        Some(&items[below(rng, items.len())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{below, choose, coin};
+    use rand_pcg::Pcg32;
+
+    #[test]
+    fn test_choices() {
+        let mut rng = Pcg32::new(1, 1);
+        let numbers = (0..20).map(|_| below(&mut rng, 3)).collect::<Vec<_>>();
+        assert!(numbers.iter().all(|&n| n < 3));
+        assert!((0..3).all(|n| numbers.contains(&n)));
+        let coins = (0..20).map(|_| coin(&mut rng)).collect::<Vec<_>>();
+        assert!(coins.contains(&true) && coins.contains(&false));
+        assert_eq!(choose(&mut rng, &[7]), Some(&7));
+        assert_eq!(choose::<u8, _>(&mut rng, &[]), None);
+    }
+}
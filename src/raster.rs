@@ -0,0 +1,378 @@
+use crate::distances::Gradient;
+use crate::grid::{Grid, Position, WALL_EAST, WALL_NORTH, WALL_SOUTH, WALL_WEST};
+use crate::grid3d::Grid3D;
+use crate::weave_grid::{Passage, WeaveGrid};
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+/// How grids are rasterized by the `to_image`/`to_png` methods.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// Distance in pixels between neighboring walls.
+    pub cell_size: u32,
+    pub wall_thickness: u32,
+    /// Empty border around the maze, in pixels.
+    pub margin: u32,
+    pub background: Rgba<u8>,
+    pub wall: Rgba<u8>,
+    /// Colours every cell by its distance from this cell instead of the background colour. For
+    /// a `Grid3D` the cell is on the lowest level.
+    pub heat_map: Option<Position>,
+    pub gradient: Gradient,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            cell_size: 20,
+            wall_thickness: 2,
+            margin: 10,
+            background: Rgba([255, 255, 255, 255]),
+            wall: Rgba([0, 0, 0, 255]),
+            heat_map: None,
+            gradient: Gradient::default(),
+        }
+    }
+}
+
+impl RenderOptions {
+    fn maze_size(&self, rows: usize, columns: usize) -> (u32, u32) {
+        (
+            columns as u32 * self.cell_size + self.wall_thickness,
+            rows as u32 * self.cell_size + self.wall_thickness,
+        )
+    }
+
+    fn canvas(&self, width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_pixel(
+            width + self.margin * 2,
+            height + self.margin * 2,
+            self.background,
+        )
+    }
+
+    /// Top-left pixel of the cell in a maze drawn at `origin`.
+    fn cell_origin(&self, origin: (u32, u32), (x, y): Position) -> (u32, u32) {
+        (
+            origin.0 + x as u32 * self.cell_size,
+            origin.1 + y as u32 * self.cell_size,
+        )
+    }
+}
+
+impl Grid {
+    pub fn to_image(&self, options: &RenderOptions) -> RgbaImage {
+        let (width, height) = options.maze_size(self.rows(), self.columns());
+        let mut image = options.canvas(width, height);
+        let origin = (options.margin, options.margin);
+        if let Some(root) = options.heat_map {
+            let distances = self.distances(root);
+            draw_heat_map(&mut image, origin, self.iter(), options, |pos| {
+                distances.color(pos, &options.gradient)
+            });
+        }
+        draw_walls(&mut image, origin, self.iter(), options, |pos| {
+            self.walls_of_cell(pos)
+        });
+        image
+    }
+
+    /// Writes the maze as a PNG image without needing a window.
+    pub fn to_png(&self, path: impl AsRef<Path>, options: RenderOptions) -> anyhow::Result<()> {
+        self.to_image(&options).save(path)?;
+        Ok(())
+    }
+}
+
+impl WeaveGrid {
+    /// Like `Grid::to_image`. Over-cells with a tunnel beneath them get a pair of rails along the
+    /// bridge, leaving a gap where the tunnel passes under.
+    pub fn to_image(&self, options: &RenderOptions) -> RgbaImage {
+        let (width, height) = options.maze_size(self.rows(), self.columns());
+        let mut image = options.canvas(width, height);
+        let origin = (options.margin, options.margin);
+        if let Some(root) = options.heat_map {
+            let distances = self.distances(root);
+            draw_heat_map(&mut image, origin, self.iter(), options, |pos| {
+                distances.color(pos, &options.gradient)
+            });
+        }
+        draw_walls(&mut image, origin, self.iter(), options, |pos| {
+            self.walls_of_cell(pos)
+        });
+
+        let size = options.cell_size;
+        let thickness = options.wall_thickness;
+        let inset = size / 4;
+        for (pos, passage) in self.under_cells() {
+            let (left, top) = options.cell_origin(origin, pos);
+            match passage {
+                Passage::Horizontal => {
+                    fill_rect(&mut image, left + inset, top, thickness, size, options.wall);
+                    fill_rect(
+                        &mut image,
+                        left + size - inset,
+                        top,
+                        thickness,
+                        size,
+                        options.wall,
+                    );
+                }
+                Passage::Vertical => {
+                    fill_rect(&mut image, left, top + inset, size, thickness, options.wall);
+                    fill_rect(
+                        &mut image,
+                        left,
+                        top + size - inset,
+                        size,
+                        thickness,
+                        options.wall,
+                    );
+                }
+            }
+        }
+        image
+    }
+
+    pub fn to_png(&self, path: impl AsRef<Path>, options: RenderOptions) -> anyhow::Result<()> {
+        self.to_image(&options).save(path)?;
+        Ok(())
+    }
+}
+
+impl Grid3D {
+    /// Like `Grid::to_image`, with the levels side by side and separated by the margin. A stair
+    /// up is marked in the top half of the cell and a stair down in the bottom half. The heat map
+    /// counts each stair climbed as one step.
+    pub fn to_image(&self, options: &RenderOptions) -> RgbaImage {
+        let (level_width, height) = options.maze_size(self.rows(), self.columns());
+        let levels = self.levels() as u32;
+        let width = level_width * levels + options.margin * levels.saturating_sub(1);
+        let mut image = options.canvas(width, height);
+
+        let distances = options.heat_map.map(|(x, y)| self.distances((x, y, 0)));
+        let size = options.cell_size;
+        let marker = (size / 4).max(1);
+        for z in 0..self.levels() as isize {
+            let level = match self.level(z) {
+                Some(level) => level,
+                None => continue,
+            };
+            let origin = (
+                options.margin + z as u32 * (level_width + options.margin),
+                options.margin,
+            );
+            if let Some(distances) = &distances {
+                draw_heat_map(&mut image, origin, level.iter(), options, |(x, y)| {
+                    distances.color((x, y, z), &options.gradient)
+                });
+            }
+            draw_walls(&mut image, origin, level.iter(), options, |pos| {
+                level.walls_of_cell(pos)
+            });
+            for (x, y) in level.iter() {
+                let (left, top) = options.cell_origin(origin, (x, y));
+                let left = left + (size - marker) / 2;
+                if self.is_linked_up((x, y, z)) {
+                    fill_rect(
+                        &mut image,
+                        left,
+                        top + size / 4,
+                        marker,
+                        marker,
+                        options.wall,
+                    );
+                }
+                if self.is_linked_down((x, y, z)) {
+                    fill_rect(
+                        &mut image,
+                        left,
+                        top + size * 3 / 4,
+                        marker,
+                        marker,
+                        options.wall,
+                    );
+                }
+            }
+        }
+        image
+    }
+
+    pub fn to_png(&self, path: impl AsRef<Path>, options: RenderOptions) -> anyhow::Result<()> {
+        self.to_image(&options).save(path)?;
+        Ok(())
+    }
+}
+
+fn draw_heat_map(
+    image: &mut RgbaImage,
+    origin: (u32, u32),
+    cells: impl Iterator<Item = Position>,
+    options: &RenderOptions,
+    color_of_cell: impl Fn(Position) -> Option<Rgba<u8>>,
+) {
+    let size = options.cell_size;
+    for pos in cells {
+        if let Some(color) = color_of_cell(pos) {
+            let (left, top) = options.cell_origin(origin, pos);
+            fill_rect(image, left, top, size, size, color);
+        }
+    }
+}
+
+fn draw_walls(
+    image: &mut RgbaImage,
+    origin: (u32, u32),
+    cells: impl Iterator<Item = Position>,
+    options: &RenderOptions,
+    walls_of_cell: impl Fn(Position) -> Option<u8>,
+) {
+    let size = options.cell_size;
+    let thickness = options.wall_thickness;
+    let color = options.wall;
+    for pos in cells {
+        let walls = walls_of_cell(pos).unwrap_or(0);
+        let (left, top) = options.cell_origin(origin, pos);
+        if walls & WALL_NORTH != 0 {
+            fill_rect(image, left, top, size + thickness, thickness, color);
+        }
+        if walls & WALL_WEST != 0 {
+            fill_rect(image, left, top, thickness, size + thickness, color);
+        }
+        if walls & WALL_EAST != 0 {
+            fill_rect(image, left + size, top, thickness, size + thickness, color);
+        }
+        if walls & WALL_SOUTH != 0 {
+            fill_rect(image, left, top + size, size + thickness, thickness, color);
+        }
+    }
+}
+
+fn fill_rect(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::distances::Gradient;
+    use crate::grid::Grid;
+    use crate::grid3d::Grid3D;
+    use crate::raster::RenderOptions;
+    use crate::sidewinder::sidewinder;
+    use crate::weave_grid::WeaveGrid;
+    use image::RgbaImage;
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    const OPTIONS: RenderOptions = RenderOptions {
+        cell_size: 4,
+        wall_thickness: 1,
+        margin: 0,
+        background: image::Rgba([255, 255, 255, 255]),
+        wall: image::Rgba([0, 0, 0, 255]),
+        heat_map: None,
+        gradient: Gradient {
+            near: image::Rgba([255, 0, 0, 255]),
+            far: image::Rgba([0, 0, 255, 255]),
+        },
+    };
+
+    /// Reads the image back as `+---+` text by sampling the middle of every wall.
+    fn to_ascii(image: &RgbaImage, rows: u32, columns: u32) -> String {
+        let is_wall = |x, y| image.get_pixel(x, y) == &OPTIONS.wall;
+        let mut out = String::new();
+        for y in 0..=rows {
+            for x in 0..columns {
+                out.push('+');
+                out.push_str(if is_wall(x * 4 + 2, y * 4) {
+                    "---"
+                } else {
+                    "   "
+                });
+            }
+            out.push_str("+\n");
+            if y == rows {
+                break;
+            }
+            for x in 0..=columns {
+                out.push(if is_wall(x * 4, y * 4 + 2) { '|' } else { ' ' });
+                if x < columns {
+                    out.push_str("   ");
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn test_image_matches_display() {
+        let mut grid = Grid::new(6, 8);
+        sidewinder(&mut grid, &mut StdRng::seed_from_u64(1)).unwrap();
+        let image = grid.to_image(&OPTIONS);
+        assert_eq!(image.dimensions(), (33, 25));
+        assert_eq!(to_ascii(&image, 6, 8), grid.to_string());
+    }
+
+    #[test]
+    fn test_weave_image_draws_bridge() {
+        let mut grid = WeaveGrid::new(3, 3);
+        grid.link_cell((0, 1), (1, 1));
+        grid.link_cell((1, 1), (2, 1));
+        grid.link_cell((1, 0), (1, 2));
+        let image = grid.to_image(&OPTIONS);
+        let walls = to_ascii(&image, 3, 3);
+        assert_eq!(walls.lines().nth(2), Some("+---+   +---+"));
+        assert_eq!(image.get_pixel(6, 5), &OPTIONS.wall);
+        assert_eq!(image.get_pixel(6, 6), &OPTIONS.background);
+        assert_eq!(image.get_pixel(6, 7), &OPTIONS.wall);
+    }
+
+    #[test]
+    fn test_heat_map() {
+        let mut grid = Grid::new(1, 3);
+        grid.link_cell((0, 0), (1, 0), true);
+        grid.link_cell((1, 0), (2, 0), true);
+        let options = RenderOptions {
+            heat_map: Some((2, 0)),
+            ..OPTIONS
+        };
+        let image = grid.to_image(&options);
+        assert_eq!(image.get_pixel(2, 2), &OPTIONS.gradient.far);
+        assert_eq!(image.get_pixel(6, 2), &OPTIONS.gradient.at(0.5));
+        assert_eq!(image.get_pixel(10, 2), &OPTIONS.gradient.near);
+        assert_eq!(to_ascii(&image, 1, 3), grid.to_string());
+    }
+
+    #[test]
+    fn test_3d_heat_map() {
+        let mut grid = Grid3D::new(2, 1, 2);
+        grid.link_cell((0, 0, 0), (1, 0, 0));
+        grid.link_cell((1, 0, 0), (1, 0, 1));
+        grid.link_cell((1, 0, 1), (0, 0, 1));
+        let options = RenderOptions {
+            heat_map: Some((0, 0)),
+            ..OPTIONS
+        };
+        let image = grid.to_image(&options);
+        // Each level is two cells and a wall wide, with no margin in between.
+        assert_eq!(image.get_pixel(2, 2), &OPTIONS.gradient.near);
+        assert_eq!(image.get_pixel(6, 2), &OPTIONS.gradient.at(1. / 3.));
+        assert_eq!(image.get_pixel(15, 2), &OPTIONS.gradient.at(2. / 3.));
+        assert_eq!(image.get_pixel(11, 2), &OPTIONS.gradient.far);
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let grid = Grid::new(2, 3);
+        let path = std::env::temp_dir().join("maze_test_png_round_trip.png");
+        grid.to_png(&path, RenderOptions::default()).unwrap();
+        let image = image::open(&path).unwrap().to_rgba8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image, grid.to_image(&RenderOptions::default()));
+    }
+}
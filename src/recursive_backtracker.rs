@@ -0,0 +1,232 @@
+use crate::algorithms::Step;
+use crate::grid::{Grid, GridError, Position};
+use crate::grid3d::{Grid3D, Grid3DError};
+use crate::random;
+use crate::weave_grid::WeaveGrid;
+use rand::Rng;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+pub fn recursive_backtracker<T: Rng>(grid: &mut Grid, rng: &mut T) -> Result<(), GridError> {
+    recursive_backtracker_traced(grid, rng, |_| {})
+}
+
+/// Like `recursive_backtracker`, reporting the head of the path whenever it moves, forwards or
+/// back, and each passage carved to `trace`.
+pub fn recursive_backtracker_traced<T: Rng>(
+    grid: &mut Grid,
+    rng: &mut T,
+    trace: impl FnMut(Step),
+) -> Result<(), GridError> {
+    let trace = RefCell::new(trace);
+    let start = match random_position(grid.rows(), grid.columns(), rng) {
+        Some(start) => start,
+        None => return Ok(()),
+    };
+    carve(
+        grid,
+        start,
+        rng,
+        |grid, pos| {
+            (trace.borrow_mut())(Step::Visit(pos));
+            vec![
+                grid.north_of_cell(pos),
+                grid.south_of_cell(pos),
+                grid.east_of_cell(pos),
+                grid.west_of_cell(pos),
+            ]
+            .into_iter()
+            .flatten()
+            .collect()
+        },
+        |grid, from, to| {
+            grid.try_link(from, to, true)?;
+            (trace.borrow_mut())(Step::Link(from, to));
+            Ok(())
+        },
+    )
+}
+
+/// Depth-first carving that may also step under a straight corridor, creating crossings.
+pub fn recursive_backtracker_weave<T: Rng>(
+    grid: &mut WeaveGrid,
+    rng: &mut T,
+) -> Result<(), GridError> {
+    let start = match random_position(grid.rows(), grid.columns(), rng) {
+        Some(start) => start,
+        None => return Ok(()),
+    };
+    carve(
+        grid,
+        start,
+        rng,
+        |grid, pos| grid.neighbors_of_cell(pos),
+        |grid, from, to| grid.try_link(from, to),
+    )
+}
+
+/// Depth-first carving through every level, climbing or descending stairs like any other move.
+pub fn recursive_backtracker_3d<T: Rng>(grid: &mut Grid3D, rng: &mut T) -> Result<(), Grid3DError> {
+    if grid.levels() == 0 {
+        return Ok(());
+    }
+    let (x, y) = match random_position(grid.rows(), grid.columns(), rng) {
+        Some(start) => start,
+        None => return Ok(()),
+    };
+    let start = (x, y, random::below(rng, grid.levels()) as isize);
+    carve(
+        grid,
+        start,
+        rng,
+        |grid, pos| grid.neighbors_of_cell(pos),
+        |grid, from, to| grid.try_link(from, to),
+    )
+}
+
+/// A random cell to start carving from, or `None` if the grid has no cells to carve.
+fn random_position<T: Rng>(rows: usize, columns: usize, rng: &mut T) -> Option<Position> {
+    if rows == 0 || columns == 0 {
+        return None;
+    }
+    Some((
+        random::below(rng, columns) as isize,
+        random::below(rng, rows) as isize,
+    ))
+}
+
+fn carve<G, P: Copy + Eq + Hash, T: Rng, E>(
+    grid: &mut G,
+    start: P,
+    rng: &mut T,
+    neighbors: impl Fn(&G, P) -> Vec<P>,
+    link: impl Fn(&mut G, P, P) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut visited = HashSet::new();
+    visited.insert(start);
+    let mut stack = vec![start];
+    while let Some(&current) = stack.last() {
+        let unvisited = neighbors(grid, current)
+            .into_iter()
+            .filter(|pos| !visited.contains(pos))
+            .collect::<Vec<_>>();
+        match random::choose(rng, &unvisited).cloned() {
+            Some(next) => {
+                link(grid, current, next)?;
+                visited.insert(next);
+                stack.push(next);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recursive_backtracker, recursive_backtracker_3d, recursive_backtracker_weave};
+    use crate::grid::Grid;
+    use crate::grid3d::Grid3D;
+    use crate::test_utils::check;
+    use crate::weave_grid::WeaveGrid;
+    use expect_test::expect;
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_recursive_backtracker() {
+        check_alg!(
+            recursive_backtracker,
+            expect![[r#"
+                +---+---+---+---+---+---+---+---+---+---+
+                |               |       |               |
+                +   +   +---+   +   +   +   +---+---+   +
+                |   |   |           |   |           |   |
+                +   +   +---+---+---+---+---+---+   +   +
+                |   |   |           |               |   |
+                +---+   +   +---+   +   +---+---+---+   +
+                |       |   |   |           |       |   |
+                +   +   +   +   +---+---+   +   +---+   +
+                |   |   |       |       |       |       |
+                +   +---+---+   +   +   +   +---+   +---+
+                |               |   |   |   |       |   |
+                +   +---+---+---+   +   +---+   +---+   +
+                |   |       |       |           |       |
+                +   +---+   +---+---+---+---+---+   +   +
+                |           |           |           |   |
+                +---+---+   +---+   +   +---+   +---+   +
+                |       |       |   |       |   |       |
+                +   +---+---+   +   +---+   +---+   +   +
+                |                   |               |   |
+                +---+---+---+---+---+---+---+---+---+---+
+            "#]]
+        );
+    }
+
+    #[test]
+    fn test_recursive_backtracker_weave() {
+        check_weave_alg!(
+            recursive_backtracker_weave,
+            expect![[r#"
+                +---+---+---+---+---+---+---+---+---+---+
+                |                           |   |       |
+                +   +---+---+---+---+---+   +   +   +   +
+                |   |                   |    === | |    |
+                +   +   +---+---+   +   +---+   +   +---+
+                |   |       |    ===    |        | |    |
+                +   +   +---+   +   +---+   +   +   +---+
+                |   |   |       |        ===    |       |
+                +   +---+   +   +---+---+   +---+---+   +
+                |       |   |   |       |   |   |       |
+                +---+   +   +   +   +   +   +   +   +---+
+                |       |   |   |   |    === ===    |   |
+                +   +---+   +---+   +---+   +   +---+   +
+                |   |       |       |       |           |
+                +   +   +---+---+---+   +---+   +   +---+
+                |    ===    |               |    ===    |
+                +---+   +   +---+---+---+   +---+   +   +
+                |   |    | |        |       |       |   |
+                +   +   +   +---+   +   +---+---+---+   +
+                |       |           |                   |
+                +---+---+---+---+---+---+---+---+---+---+
+            "#]]
+        );
+    }
+
+    #[test]
+    fn test_recursive_backtracker_3d() {
+        let mut grid = Grid3D::new(3, 4, 4);
+        let mut rng = StdRng::seed_from_u64(1);
+        recursive_backtracker_3d(&mut grid, &mut rng).unwrap();
+        check(
+            &grid,
+            expect![[r#"
+                +---+---+---+---+   +---+---+---+---+   +---+---+---+---+
+                | U |     U | U |   |U D| U | D |U D|   | D | D       D |
+                +   +   +---+   +   +---+   +   +---+   +   +---+---+---+
+                |   |   |       |   | U |   |     U |   | D         | D |
+                +   +   +   +---+   +   +   +---+---+   +---+---+---+   +
+                |   |       | U |   |   |   |     D |   |               |
+                +   +---+---+   +   +---+---+---+---+   +   +---+---+---+
+                | U   U |     U |   |U D| D      U D|   | D |         D |
+                +---+---+---+---+   +---+---+---+---+   +---+---+---+---+
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_empty_grids() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut grid = Grid::new(0, 3);
+        assert_eq!(recursive_backtracker(&mut grid, &mut rng), Ok(()));
+        let mut grid = WeaveGrid::new(3, 0);
+        assert_eq!(recursive_backtracker_weave(&mut grid, &mut rng), Ok(()));
+        let mut grid = Grid3D::new(0, 3, 3);
+        assert_eq!(recursive_backtracker_3d(&mut grid, &mut rng), Ok(()));
+        let mut grid = Grid3D::new(2, 0, 3);
+        assert_eq!(recursive_backtracker_3d(&mut grid, &mut rng), Ok(()));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::grid::{Direction, Grid, Position};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt;
+
+const JSON_VERSION: u32 = 1;
+
+const MAGIC: &[u8; 4] = b"MAZE";
+const BINARY_VERSION: u8 = 1;
+/// Magic, version byte, then rows and columns as little-endian `u32`s.
+const HEADER_LEN: usize = 4 + 1 + 4 + 4;
+
+const OPEN_EAST: u8 = 0b01;
+const OPEN_SOUTH: u8 = 0b10;
+
+/// The JSON layout: the linked directions of every cell in row-major order.
+#[derive(Serialize, Deserialize)]
+struct GridData {
+    version: u32,
+    rows: usize,
+    columns: usize,
+    cells: Vec<Vec<Direction>>,
+}
+
+impl Serialize for Grid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let cells = self
+            .iter()
+            .filter_map(|pos| self.cell(pos))
+            .map(|cell| cell.open_directions())
+            .collect();
+        GridData {
+            version: JSON_VERSION,
+            rows: self.rows(),
+            columns: self.columns(),
+            cells,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Grid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = GridData::deserialize(deserializer)?;
+        if data.version != JSON_VERSION {
+            return Err(D::Error::custom(format!(
+                "unsupported maze version {}",
+                data.version
+            )));
+        }
+        let cells = data.rows.checked_mul(data.columns).ok_or_else(|| {
+            D::Error::custom(format!(
+                "a {}x{} maze is too large",
+                data.rows, data.columns
+            ))
+        })?;
+        if data.cells.len() != cells {
+            return Err(D::Error::custom(format!(
+                "expected {} cells for a {}x{} maze, found {}",
+                cells,
+                data.rows,
+                data.columns,
+                data.cells.len()
+            )));
+        }
+
+        let mut grid = Grid::new(data.rows, data.columns);
+        for (pos, directions) in grid.iter().zip(data.cells.iter()) {
+            for direction in directions {
+                let neighbor = grid.neighbor_of_cell(pos, *direction).ok_or_else(|| {
+                    D::Error::custom(format!(
+                        "cell {:?} links {:?} out of the maze",
+                        pos, direction
+                    ))
+                })?;
+                grid.link_cell(pos, neighbor, true);
+            }
+        }
+        Ok(grid)
+    }
+}
+
+/// Why `Grid::from_bytes` rejected its input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DecodeGridError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    TooLarge { rows: usize, columns: usize },
+    WrongLength { expected: usize, found: usize },
+    LinkOutOfGrid(Position),
+}
+
+impl fmt::Display for DecodeGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeGridError::BadMagic => write!(f, "not a maze file"),
+            DecodeGridError::UnsupportedVersion(v) => write!(f, "unsupported maze version {}", v),
+            DecodeGridError::TooLarge { rows, columns } => {
+                write!(f, "a {}x{} maze is too large", rows, columns)
+            }
+            DecodeGridError::WrongLength { expected, found } => {
+                write!(f, "expected {} bytes, found {}", expected, found)
+            }
+            DecodeGridError::LinkOutOfGrid(pos) => {
+                write!(f, "cell {:?} links out of the maze", pos)
+            }
+        }
+    }
+}
+
+impl Error for DecodeGridError {}
+
+impl Grid {
+    /// Packs the maze into a versioned header followed by two bits per cell, row-major and
+    /// least significant bits first: whether the east and the south walls are open.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let cells = self.rows() * self.columns();
+        let mut bytes = Vec::with_capacity(HEADER_LEN + cells.div_ceil(4));
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(BINARY_VERSION);
+        bytes.extend_from_slice(&(self.rows() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.columns() as u32).to_le_bytes());
+        bytes.resize(HEADER_LEN + cells.div_ceil(4), 0);
+
+        for (i, pos) in self.iter().enumerate() {
+            let mut bits = 0;
+            if self.is_linked_optional(pos, self.east_of_cell(pos)) {
+                bits |= OPEN_EAST;
+            }
+            if self.is_linked_optional(pos, self.south_of_cell(pos)) {
+                bits |= OPEN_SOUTH;
+            }
+            bytes[HEADER_LEN + i / 4] |= bits << (i % 4 * 2);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Grid, DecodeGridError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(DecodeGridError::WrongLength {
+                expected: HEADER_LEN,
+                found: bytes.len(),
+            });
+        }
+        if &bytes[..4] != MAGIC {
+            return Err(DecodeGridError::BadMagic);
+        }
+        if bytes[4] != BINARY_VERSION {
+            return Err(DecodeGridError::UnsupportedVersion(bytes[4]));
+        }
+        let rows = u32::from_le_bytes(bytes[5..9].try_into().unwrap()) as usize;
+        let columns = u32::from_le_bytes(bytes[9..13].try_into().unwrap()) as usize;
+        let expected = rows
+            .checked_mul(columns)
+            .map(|cells| HEADER_LEN + cells.div_ceil(4))
+            .ok_or(DecodeGridError::TooLarge { rows, columns })?;
+        if bytes.len() != expected {
+            return Err(DecodeGridError::WrongLength {
+                expected,
+                found: bytes.len(),
+            });
+        }
+
+        let mut grid = Grid::new(rows, columns);
+        for (i, pos) in grid.iter().enumerate() {
+            let bits = bytes[HEADER_LEN + i / 4] >> (i % 4 * 2);
+            for (bit, neighbor) in [
+                (OPEN_EAST, grid.east_of_cell(pos)),
+                (OPEN_SOUTH, grid.south_of_cell(pos)),
+            ]
+            .iter()
+            {
+                if bits & bit != 0 {
+                    let neighbor = neighbor.ok_or(DecodeGridError::LinkOutOfGrid(pos))?;
+                    grid.link_cell(pos, neighbor, true);
+                }
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+    use crate::serialize::DecodeGridError;
+    use crate::sidewinder::sidewinder;
+    use crate::test_utils::{check, check_debug};
+    use expect_test::expect;
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    fn small_maze() -> Grid {
+        "\
++---+---+---+
+|       |   |
++---+   +   +
+|           |
++---+---+---+
+"
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_json() {
+        let grid = small_maze();
+        let json = serde_json::to_string(&grid).unwrap();
+        check(
+            &json,
+            expect![[
+                r#"{"version":1,"rows":2,"columns":3,"cells":[["east"],["south","west"],["south"],["east"],["north","east","west"],["north","west"]]}"#
+            ]],
+        );
+        assert_eq!(serde_json::from_str::<Grid>(&json).unwrap(), grid);
+
+        let mut maze = Grid::new(8, 8);
+        sidewinder(&mut maze, &mut StdRng::seed_from_u64(1)).unwrap();
+        let json = serde_json::to_string(&maze).unwrap();
+        assert_eq!(serde_json::from_str::<Grid>(&json).unwrap(), maze);
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let err = |json: &str| serde_json::from_str::<Grid>(json).unwrap_err().to_string();
+        assert_eq!(
+            err(r#"{"version":2,"rows":1,"columns":1,"cells":[[]]}"#),
+            "unsupported maze version 2"
+        );
+        assert_eq!(
+            err(r#"{"version":1,"rows":1,"columns":2,"cells":[[]]}"#),
+            "expected 2 cells for a 1x2 maze, found 1"
+        );
+        assert_eq!(
+            err(r#"{"version":1,"rows":18446744073709551615,"columns":2,"cells":[]}"#),
+            "a 18446744073709551615x2 maze is too large"
+        );
+        assert_eq!(
+            err(r#"{"version":1,"rows":1,"columns":1,"cells":[["north"]]}"#),
+            "cell (0, 0) links North out of the maze"
+        );
+    }
+
+    #[test]
+    fn test_bytes() {
+        let grid = small_maze();
+        let bytes = grid.to_bytes();
+        check_debug(
+            &bytes,
+            expect![[r#"
+            [
+                77,
+                65,
+                90,
+                69,
+                1,
+                2,
+                0,
+                0,
+                0,
+                3,
+                0,
+                0,
+                0,
+                105,
+                1,
+            ]
+        "#]],
+        );
+        assert_eq!(Grid::from_bytes(&bytes), Ok(grid));
+
+        let mut maze = Grid::new(7, 9);
+        sidewinder(&mut maze, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(Grid::from_bytes(&maze.to_bytes()), Ok(maze));
+    }
+
+    #[test]
+    fn test_bytes_errors() {
+        let bytes = small_maze().to_bytes();
+        assert_eq!(
+            Grid::from_bytes(&bytes[..3]),
+            Err(DecodeGridError::WrongLength {
+                expected: 13,
+                found: 3
+            })
+        );
+        assert_eq!(
+            Grid::from_bytes(b"MAZF\x01\0\0\0\0\0\0\0\0"),
+            Err(DecodeGridError::BadMagic)
+        );
+        assert_eq!(
+            Grid::from_bytes(b"MAZE\x02\0\0\0\0\0\0\0\0"),
+            Err(DecodeGridError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            Grid::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DecodeGridError::WrongLength {
+                expected: 15,
+                found: 14
+            })
+        );
+        assert_eq!(
+            Grid::from_bytes(b"MAZE\x01\x01\0\0\0\x01\0\0\0\x01"),
+            Err(DecodeGridError::LinkOutOfGrid((0, 0)))
+        );
+    }
+}
@@ -1,33 +1,94 @@
-use crate::grid::Grid;
-use rand::prelude::SliceRandom;
+use crate::algorithms::Step;
+use crate::grid::{Grid, GridError};
+use crate::random;
+use crate::weave_grid::WeaveGrid;
 use rand::Rng;
 
-pub fn sidewinder<T: Rng>(grid: &mut Grid, rng: &mut T) {
+pub fn sidewinder<T: Rng>(grid: &mut Grid, rng: &mut T) -> Result<(), GridError> {
+    sidewinder_with(grid, rng, None::<fn(Step)>)
+}
+
+/// Like `sidewinder`, reporting the run after every cell and each passage carved to `trace`.
+pub fn sidewinder_traced<T: Rng>(
+    grid: &mut Grid,
+    rng: &mut T,
+    trace: impl FnMut(Step),
+) -> Result<(), GridError> {
+    sidewinder_with(grid, rng, Some(trace))
+}
+
+/// Copying the run for `Step::Run` costs as much as the carving itself, so it is only done
+/// when something is tracing.
+fn sidewinder_with<T: Rng>(
+    grid: &mut Grid,
+    rng: &mut T,
+    mut trace: Option<impl FnMut(Step)>,
+) -> Result<(), GridError> {
+    let mut trace = |step: &dyn Fn() -> Step| {
+        if let Some(trace) = trace.as_mut() {
+            trace(step());
+        }
+    };
     let mut run = vec![];
     for row in grid.iter_rows() {
         run.clear();
         for pos in row {
             run.push(pos);
+            trace(&|| Step::Run(run.clone()));
             let at_eastern_boundary = grid.east_of_cell(pos).is_none();
             let at_northern_boundary = grid.north_of_cell(pos).is_none();
 
             let should_close_out =
-                at_eastern_boundary || (!at_northern_boundary && rng.gen::<bool>());
+                at_eastern_boundary || (!at_northern_boundary && random::coin(rng));
             if should_close_out {
-                if let Some(member) = run.choose(rng).cloned() {
-                    grid.link_cell_to_north(member);
+                if let Some(member) = random::choose(rng, &run).cloned() {
+                    if let Some(north) = grid.north_of_cell(member) {
+                        grid.try_link(member, north, true)?;
+                        trace(&|| Step::Link(member, north));
+                    }
                 }
                 run.clear();
-            } else {
-                grid.link_cell_to_east(pos);
+            } else if let Some(east) = grid.east_of_cell(pos) {
+                grid.try_link(pos, east, true)?;
+                trace(&|| Step::Link(pos, east));
             }
         }
     }
+    Ok(())
+}
+
+/// Like `sidewinder`, but a run may close out by tunnelling under a finished east-west corridor
+/// to its north.
+pub fn sidewinder_weave<T: Rng>(grid: &mut WeaveGrid, rng: &mut T) -> Result<(), GridError> {
+    let mut run = vec![];
+    for row in grid.iter_rows() {
+        run.clear();
+        for pos in row {
+            run.push(pos);
+            let at_eastern_boundary = grid.east_of_cell(pos).is_none();
+            let at_northern_boundary = grid.north_of_cell(pos).is_none();
+
+            let should_close_out =
+                at_eastern_boundary || (!at_northern_boundary && random::coin(rng));
+            if should_close_out {
+                if let Some(member) = random::choose(rng, &run).cloned() {
+                    let tunnel = grid.can_tunnel_north(member).filter(|_| random::coin(rng));
+                    if let Some(north) = tunnel.or_else(|| grid.north_of_cell(member)) {
+                        grid.try_link(member, north)?;
+                    }
+                }
+                run.clear();
+            } else if let Some(east) = grid.east_of_cell(pos) {
+                grid.try_link(pos, east)?;
+            }
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::sidewinder;
+    use super::{sidewinder, sidewinder_weave};
     use expect_test::expect;
 
     #[test]
@@ -35,25 +96,55 @@ mod tests {
         check_alg!(sidewinder, expect![[r#"
             +---+---+---+---+---+---+---+---+---+---+
             |                                       |
-            +   +   +---+   +---+---+---+---+   +   +
-            |   |       |           |           |   |
-            +   +---+   +---+---+   +---+   +---+   +
-            |   |               |   |       |       |
-            +---+   +---+   +   +---+---+---+   +   +
-            |       |       |   |               |   |
-            +   +   +   +   +---+---+   +---+---+   +
-            |   |   |   |       |       |           |
-            +   +   +---+   +   +---+---+   +   +   +
-            |   |       |   |           |   |   |   |
-            +   +   +---+---+   +---+---+---+   +---+
-            |   |   |               |               |
-            +   +---+   +---+   +   +---+---+---+   +
-            |       |       |   |   |               |
-            +   +   +   +   +---+---+   +---+---+   +
-            |   |   |   |   |                   |   |
-            +---+   +---+   +   +---+---+---+---+   +
-            |       |       |           |           |
+            +   +---+   +---+---+   +---+---+   +---+
+            |   |           |       |               |
+            +---+---+---+---+   +---+---+   +   +   +
+            |                       |       |   |   |
+            +   +---+   +---+---+   +---+   +   +   +
+            |   |               |   |       |   |   |
+            +   +   +   +   +   +---+---+   +---+---+
+            |   |   |   |   |   |                   |
+            +   +   +   +   +   +---+---+   +   +---+
+            |   |   |   |   |       |       |       |
+            +   +   +   +---+---+   +   +---+   +---+
+            |   |   |   |           |       |       |
+            +   +---+   +---+   +---+   +---+   +   +
+            |   |       |       |       |       |   |
+            +---+   +   +---+---+---+   +---+   +---+
+            |       |   |                   |       |
+            +   +---+   +---+   +---+   +---+   +   +
+            |   |           |       |       |   |   |
             +---+---+---+---+---+---+---+---+---+---+
         "#]]);
     }
+
+    #[test]
+    fn test_sidewinder_weave() {
+        check_weave_alg!(
+            sidewinder_weave,
+            expect![[r#"
+                +---+---+---+---+---+---+---+---+---+---+
+                |                                       |
+                +   +---+   +---+---+   +---+   +   +---+
+                |   |           |       |    ===        |
+                +---+   +---+---+   +---+---+   +   +   +
+                |    ===                |       |   |   |
+                +---+   +   +   +---+---+---+   +   +   +
+                |        === ===    |           |   |   |
+                +   +   +   +   +---+---+---+   +---+   +
+                |   |   |   |   |                   |   |
+                +   +   +   +   +---+---+   +---+   +   +
+                |   |   |   |       |       |       |   |
+                +   +   +---+   +   +---+   +   +---+   +
+                |   |   |       |       |    ===    |   |
+                +   +---+   +---+   +---+   +   +   +   +
+                |   |       |       |       |   |   |   |
+                +---+---+---+   +---+   +---+---+   +---+
+                |                   |           |       |
+                +   +   +---+   +   +---+---+   +   +   +
+                |   |       |   |   |           |   |   |
+                +---+---+---+---+---+---+---+---+---+---+
+            "#]]
+        );
+    }
 }
@@ -0,0 +1,224 @@
+use crate::grid::{Grid, Position};
+use crate::raster::RenderOptions;
+use image::Rgba;
+use std::fmt::Write;
+
+const SOLUTION_COLOR: Rgba<u8> = Rgba([220, 40, 40, 255]);
+const START_COLOR: Rgba<u8> = Rgba([40, 160, 40, 255]);
+const GOAL_COLOR: Rgba<u8> = Rgba([220, 40, 40, 255]);
+
+/// Optional `<g>` layers drawn by `Grid::to_svg` in addition to the walls.
+#[derive(Debug, Clone, Default)]
+pub struct SvgLayers {
+    /// Cells of a path drawn as a line through their centres.
+    pub solution: Option<Vec<Position>>,
+    pub start: Option<Position>,
+    pub goal: Option<Position>,
+}
+
+impl Grid {
+    /// Renders the maze as SVG with the same geometry and heat map as `to_image`. All walls are
+    /// a single `<path>`, with collinear wall segments merged into one line.
+    pub fn to_svg(&self, options: &RenderOptions, layers: &SvgLayers) -> String {
+        let size = options.cell_size as f32;
+        let thickness = options.wall_thickness as f32;
+        let width = self.columns() as f32 * size + thickness + options.margin as f32 * 2.;
+        let height = self.rows() as f32 * size + thickness + options.margin as f32 * 2.;
+        let offset = options.margin as f32 + thickness / 2.;
+        let center = |(x, y): Position| {
+            (
+                offset + (x as f32 + 0.5) * size,
+                offset + (y as f32 + 0.5) * size,
+            )
+        };
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <rect width="100%" height="100%" fill="{}"/>"#,
+            hex(options.background)
+        )
+        .unwrap();
+
+        if let Some(root) = options.heat_map {
+            let distances = self.distances(root);
+            writeln!(svg, r#"  <g id="distances">"#).unwrap();
+            for pos in self.iter() {
+                if let Some(color) = distances.color(pos, &options.gradient) {
+                    let (x, y) = center(pos);
+                    writeln!(
+                        svg,
+                        r#"    <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                        x - size / 2.,
+                        y - size / 2.,
+                        size,
+                        size,
+                        hex(color)
+                    )
+                    .unwrap();
+                }
+            }
+            writeln!(svg, "  </g>").unwrap();
+        }
+
+        writeln!(svg, r#"  <g id="walls">"#).unwrap();
+        writeln!(
+            svg,
+            r#"    <path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="square"/>"#,
+            wall_path(self, offset, size),
+            hex(options.wall),
+            thickness
+        )
+        .unwrap();
+        writeln!(svg, "  </g>").unwrap();
+
+        if let Some(solution) = &layers.solution {
+            let points = solution
+                .iter()
+                .map(|pos| {
+                    let (x, y) = center(*pos);
+                    format!("{},{}", x, y)
+                })
+                .collect::<Vec<_>>();
+            writeln!(svg, r#"  <g id="solution">"#).unwrap();
+            writeln!(
+                svg,
+                r#"    <polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                points.join(" "),
+                hex(SOLUTION_COLOR),
+                size / 4.
+            )
+            .unwrap();
+            writeln!(svg, "  </g>").unwrap();
+        }
+
+        if layers.start.is_some() || layers.goal.is_some() {
+            writeln!(svg, r#"  <g id="markers">"#).unwrap();
+            for (pos, color) in [(layers.start, START_COLOR), (layers.goal, GOAL_COLOR)].iter() {
+                if let Some(pos) = pos {
+                    let (x, y) = center(*pos);
+                    writeln!(
+                        svg,
+                        r#"    <circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                        x,
+                        y,
+                        size * 3. / 8.,
+                        hex(*color)
+                    )
+                    .unwrap();
+                }
+            }
+            writeln!(svg, "  </g>").unwrap();
+        }
+
+        writeln!(svg, "</svg>").unwrap();
+        svg
+    }
+}
+
+/// Path data for every wall, one `M ... H`/`M ... V` command per straight run.
+fn wall_path(grid: &Grid, offset: f32, size: f32) -> String {
+    grid.wall_segments()
+        .iter()
+        .map(|segment| {
+            let (x, y) = segment.start;
+            if segment.is_horizontal() {
+                format!(
+                    "M{} {}H{}",
+                    offset + x as f32 * size,
+                    offset + y as f32 * size,
+                    offset + segment.end.0 as f32 * size
+                )
+            } else {
+                format!(
+                    "M{} {}V{}",
+                    offset + x as f32 * size,
+                    offset + y as f32 * size,
+                    offset + segment.end.1 as f32 * size
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn hex(color: Rgba<u8>) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+    use crate::raster::RenderOptions;
+    use crate::svg::SvgLayers;
+    use crate::test_utils::check;
+    use expect_test::expect;
+
+    #[test]
+    fn test_svg() {
+        let grid = "\
++---+---+---+
+|           |
++---+---+   +
+|           |
++---+---+---+
+"
+        .parse::<Grid>()
+        .unwrap();
+        check(
+            &grid.to_svg(&RenderOptions::default(), &SvgLayers::default()),
+            expect![[r##"
+                <svg xmlns="http://www.w3.org/2000/svg" width="82" height="62" viewBox="0 0 82 62">
+                  <rect width="100%" height="100%" fill="#ffffff"/>
+                  <g id="walls">
+                    <path d="M11 11H71 M11 31H51 M11 51H71 M11 11V51 M71 11V51" fill="none" stroke="#000000" stroke-width="2" stroke-linecap="square"/>
+                  </g>
+                </svg>
+            "##]],
+        );
+
+        let distances = grid.distances((0, 0));
+        let layers = SvgLayers {
+            solution: distances.path_to(&grid, (0, 1)),
+            start: Some((0, 0)),
+            goal: Some((0, 1)),
+        };
+        let options = RenderOptions {
+            margin: 0,
+            heat_map: Some((0, 0)),
+            ..RenderOptions::default()
+        };
+        check(
+            &grid.to_svg(&options, &layers),
+            expect![[r##"
+                <svg xmlns="http://www.w3.org/2000/svg" width="62" height="42" viewBox="0 0 62 42">
+                  <rect width="100%" height="100%" fill="#ffffff"/>
+                  <g id="distances">
+                    <rect x="1" y="1" width="20" height="20" fill="#faf0b4"/>
+                    <rect x="21" y="1" width="20" height="20" fill="#ccd2b0"/>
+                    <rect x="41" y="1" width="20" height="20" fill="#9eb4ac"/>
+                    <rect x="1" y="21" width="20" height="20" fill="#145aa0"/>
+                    <rect x="21" y="21" width="20" height="20" fill="#4278a4"/>
+                    <rect x="41" y="21" width="20" height="20" fill="#7096a8"/>
+                  </g>
+                  <g id="walls">
+                    <path d="M1 1H61 M1 21H41 M1 41H61 M1 1V41 M61 1V41" fill="none" stroke="#000000" stroke-width="2" stroke-linecap="square"/>
+                  </g>
+                  <g id="solution">
+                    <polyline points="11,11 31,11 51,11 51,31 31,31 11,31" fill="none" stroke="#dc2828" stroke-width="5" stroke-linecap="round" stroke-linejoin="round"/>
+                  </g>
+                  <g id="markers">
+                    <circle cx="11" cy="11" r="7.5" fill="#28a028"/>
+                    <circle cx="11" cy="31" r="7.5" fill="#dc2828"/>
+                  </g>
+                </svg>
+            "##]],
+        );
+    }
+}
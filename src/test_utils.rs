@@ -16,7 +16,20 @@ macro_rules! check_alg {
         let mut grid = $crate::grid::Grid::new(10, 10);
         let mut rng = rand::prelude::StdRng::seed_from_u64(1);
 
-        $alg(&mut grid, &mut rng);
+        $alg(&mut grid, &mut rng).unwrap();
+        $crate::test_utils::check(&grid, $expected);
+        assert_eq!(grid.to_string().parse::<$crate::grid::Grid>(), Ok(grid));
+    }};
+}
+
+macro_rules! check_weave_alg {
+    ($alg: ident, $expected: expr) => {{
+        use rand::SeedableRng;
+
+        let mut grid = $crate::weave_grid::WeaveGrid::new(10, 10);
+        let mut rng = rand::prelude::StdRng::seed_from_u64(1);
+
+        $alg(&mut grid, &mut rng).unwrap();
         $crate::test_utils::check(&grid, $expected);
     }};
 }
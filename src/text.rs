@@ -0,0 +1,192 @@
+use crate::grid::Grid;
+use std::fmt;
+
+/// The text renderings available through `Grid::to_text`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TextStyle {
+    /// The `+---+` layout of `{}`.
+    Ascii,
+    /// Box-drawing characters with the matching junction at every corner, as `{:#}`.
+    BoxDrawing,
+    /// Walls as block pixels, so that each cell takes a single character.
+    HalfBlock,
+}
+
+impl Grid {
+    pub fn to_text(&self, style: TextStyle) -> String {
+        match style {
+            TextStyle::Ascii => format!("{}", self),
+            TextStyle::BoxDrawing => format!("{:#}", self),
+            TextStyle::HalfBlock => {
+                let mut out = String::new();
+                write_half_block(&mut out, self).unwrap();
+                out
+            }
+        }
+    }
+}
+
+/// The junction glyph for a corner with walls leaving it in the given directions.
+fn junction(up: bool, down: bool, left: bool, right: bool) -> char {
+    match (up, down, left, right) {
+        (false, false, false, false) => ' ',
+        (false, false, true, false) => '╴',
+        (false, false, false, true) => '╶',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╷',
+        (false, false, true, true) => '─',
+        (true, true, false, false) => '│',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+pub(crate) fn write_box_drawing(f: &mut dyn fmt::Write, grid: &Grid) -> fmt::Result {
+    let rows = grid.rows() as isize;
+    let columns = grid.columns() as isize;
+    for y in 0..=rows {
+        let mut corners = String::new();
+        for x in 0..=columns {
+            let up = y > 0 && grid.is_wall_west_of((x, y - 1));
+            let down = y < rows && grid.is_wall_west_of((x, y));
+            let left = x > 0 && grid.is_wall_north_of((x - 1, y));
+            let right = x < columns && grid.is_wall_north_of((x, y));
+            corners.push(junction(up, down, left, right));
+            if x < columns {
+                corners.push_str(if right { "───" } else { "   " });
+            }
+        }
+        writeln!(f, "{}", corners)?;
+
+        if y < rows {
+            let mut cells = String::new();
+            for x in 0..=columns {
+                cells.push(if grid.is_wall_west_of((x, y)) {
+                    '│'
+                } else {
+                    ' '
+                });
+                if x < columns {
+                    cells.push_str("   ");
+                }
+            }
+            writeln!(f, "{}", cells)?;
+        }
+    }
+    Ok(())
+}
+
+/// The quadrant block glyph with the given quadrants filled: bit 0 for the top left, 1 for the
+/// top right, 2 for the bottom left and 3 for the bottom right.
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// Draws the maze as `2 * rows + 1` by `2 * columns + 1` pixels, cells on odd coordinates and
+/// walls between them, packing two by two pixels into each quadrant block character. Character
+/// `(x, y)` is cell `(x, y)` in its bottom right quadrant, with its north and west walls and the
+/// corner between them, so every cell is one character; the last line and column close the
+/// south and east borders.
+fn write_half_block(f: &mut dyn fmt::Write, grid: &Grid) -> fmt::Result {
+    let rows = grid.rows() as isize;
+    let columns = grid.columns() as isize;
+    let is_wall = |px: isize, py: isize| -> bool {
+        let (x, y) = (px / 2, py / 2);
+        match (px % 2 == 0, py % 2 == 0) {
+            (true, true) => {
+                (y > 0 && grid.is_wall_west_of((x, y - 1)))
+                    || (y < rows && grid.is_wall_west_of((x, y)))
+                    || (x > 0 && grid.is_wall_north_of((x - 1, y)))
+                    || (x < columns && grid.is_wall_north_of((x, y)))
+            }
+            (false, true) => grid.is_wall_north_of((x, y)),
+            (true, false) => grid.is_wall_west_of((x, y)),
+            (false, false) => false,
+        }
+    };
+
+    for y in 0..=rows {
+        let mut line = String::new();
+        for x in 0..=columns {
+            let quadrants = [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .iter()
+                .enumerate()
+                .filter(|(_, (dx, dy))| is_wall(x * 2 + dx, y * 2 + dy))
+                .fold(0, |bits, (i, _)| bits | 1 << i);
+            line.push(QUADRANTS[quadrants]);
+        }
+        writeln!(f, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+    use crate::sidewinder::sidewinder;
+    use crate::test_utils::check;
+    use crate::text::TextStyle;
+    use expect_test::expect;
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    fn maze() -> Grid {
+        let mut grid = Grid::new(4, 6);
+        sidewinder(&mut grid, &mut StdRng::seed_from_u64(3)).unwrap();
+        grid
+    }
+
+    #[test]
+    fn test_box_drawing() {
+        let grid = maze();
+        check(
+            &grid.to_text(TextStyle::Ascii),
+            expect![[r#"
+                +---+---+---+---+---+---+
+                |                       |
+                +   +---+---+   +---+   +
+                |   |               |   |
+                +   +   +   +   +---+   +
+                |   |   |   |       |   |
+                +   +   +---+---+   +   +
+                |   |   |           |   |
+                +---+---+---+---+---+---+
+            "#]],
+        );
+        check(
+            &grid.to_text(TextStyle::BoxDrawing),
+            expect![[r#"
+                ┌───────────────────────┐
+                │                       │
+                │   ┌───────╴   ╶───┐   │
+                │   │               │   │
+                │   │   ╷   ╷   ╶───┤   │
+                │   │   │   │       │   │
+                │   │   ├───┴───╴   │   │
+                │   │   │           │   │
+                └───┴───┴───────────┴───┘
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_half_block() {
+        check(
+            &maze().to_text(TextStyle::HalfBlock),
+            expect![[r#"
+                ▛▀▀▀▀▀▌
+                ▌▛▀▘▀▌▌
+                ▌▌▌▌▀▌▌
+                ▌▌▛▀▘▌▌
+                ▀▀▀▀▀▀▘
+            "#]],
+        );
+    }
+}
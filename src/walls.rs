@@ -0,0 +1,210 @@
+use crate::grid::{Direction, Grid, Position, WALL_EAST, WALL_NORTH, WALL_SOUTH, WALL_WEST};
+
+/// Corner bits of `Grid::tile_mask`, above the `WALL_*` bits.
+pub const CORNER_NORTH_EAST: u8 = 0b0001_0000;
+pub const CORNER_SOUTH_EAST: u8 = 0b0010_0000;
+pub const CORNER_SOUTH_WEST: u8 = 0b0100_0000;
+pub const CORNER_NORTH_WEST: u8 = 0b1000_0000;
+/// The `WALL_*` bits of a tile mask.
+pub const TILE_WALLS: u8 = 0b0000_1111;
+
+/// A straight run of wall along the grid lines. Corner `(x, y)` is the top-left corner of cell
+/// `(x, y)`, so the corners of a maze go from `(0, 0)` to `(columns, rows)`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WallSegment {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl WallSegment {
+    pub fn is_horizontal(&self) -> bool {
+        self.start.1 == self.end.1
+    }
+
+    /// The top-left and bottom-right corners of the wall drawn `thickness` cells thick, in cell
+    /// units with y pointing down. Both ends overhang by half the thickness so that walls
+    /// meeting at a corner close it.
+    pub fn rect(&self, thickness: f32) -> ((f32, f32), (f32, f32)) {
+        let half = thickness / 2.;
+        (
+            (self.start.0 as f32 - half, self.start.1 as f32 - half),
+            (self.end.0 as f32 + half, self.end.1 as f32 + half),
+        )
+    }
+}
+
+impl Grid {
+    /// Every wall of the maze with collinear walls merged: the horizontal ones row by row from
+    /// the top, then the vertical ones column by column from the left. Derived from
+    /// `walls_of_cell`, like the sprites and images.
+    pub fn wall_segments(&self) -> Vec<WallSegment> {
+        let rows = self.rows() as isize;
+        let columns = self.columns() as isize;
+        let mut segments = vec![];
+        for y in 0..=rows {
+            for (start, end) in runs(columns, |x| self.is_wall_north_of((x, y))) {
+                segments.push(WallSegment {
+                    start: (start, y),
+                    end: (end, y),
+                });
+            }
+        }
+        for x in 0..=columns {
+            for (start, end) in runs(rows, |y| self.is_wall_west_of((x, y))) {
+                segments.push(WallSegment {
+                    start: (x, start),
+                    end: (x, end),
+                });
+            }
+        }
+        segments
+    }
+
+    /// What a tile drawn for the cell on its own has to show, for tilesets where every cell has
+    /// a tile of its own. The `WALL_*` bits are set for every closed side of the cell, including
+    /// the ones shared with its neighbors. A `CORNER_*` bit is set where walls from outside the
+    /// cell meet at one of its corners but neither of the cell's walls along that corner does,
+    /// so that the tile can fill in the end of the wall.
+    pub fn tile_mask(&self, (x, y): Position) -> Option<u8> {
+        let cell = self.cell((x, y))?;
+        let mut mask = 0;
+        for (direction, wall) in [
+            (Direction::North, WALL_NORTH),
+            (Direction::South, WALL_SOUTH),
+            (Direction::East, WALL_EAST),
+            (Direction::West, WALL_WEST),
+        ]
+        .iter()
+        {
+            if !cell.is_open(*direction) {
+                mask |= wall;
+            }
+        }
+        let corners = [
+            (
+                CORNER_NORTH_EAST,
+                WALL_NORTH | WALL_EAST,
+                self.is_wall_west_of((x + 1, y - 1)) || self.is_wall_north_of((x + 1, y)),
+            ),
+            (
+                CORNER_SOUTH_EAST,
+                WALL_SOUTH | WALL_EAST,
+                self.is_wall_west_of((x + 1, y + 1)) || self.is_wall_north_of((x + 1, y + 1)),
+            ),
+            (
+                CORNER_SOUTH_WEST,
+                WALL_SOUTH | WALL_WEST,
+                self.is_wall_west_of((x, y + 1)) || self.is_wall_north_of((x - 1, y + 1)),
+            ),
+            (
+                CORNER_NORTH_WEST,
+                WALL_NORTH | WALL_WEST,
+                self.is_wall_west_of((x, y - 1)) || self.is_wall_north_of((x - 1, y)),
+            ),
+        ];
+        for (corner, own_walls, outside) in corners.iter() {
+            if *outside && mask & own_walls == 0 {
+                mask |= corner;
+            }
+        }
+        Some(mask)
+    }
+}
+
+/// Maximal ranges `start..end` of consecutive segments in `0..len` for which `has_wall` holds.
+fn runs(len: isize, has_wall: impl Fn(isize) -> bool) -> Vec<(isize, isize)> {
+    let mut runs = vec![];
+    let mut start = None;
+    for i in 0..len {
+        match (start, has_wall(i)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                runs.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        runs.push((s, len));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{Grid, WALL_EAST, WALL_NORTH, WALL_SOUTH, WALL_WEST};
+    use crate::walls::{
+        runs, WallSegment, CORNER_NORTH_EAST, CORNER_SOUTH_EAST, CORNER_SOUTH_WEST,
+    };
+
+    #[test]
+    fn test_runs() {
+        assert_eq!(runs(6, |i| i != 2 && i != 3), vec![(0, 2), (4, 6)]);
+        assert_eq!(runs(3, |_| false), vec![]);
+    }
+
+    #[test]
+    fn test_wall_segments() {
+        let mut grid = Grid::new(2, 2);
+        grid.link_cell((0, 0), (1, 0), true);
+        grid.link_cell((1, 0), (1, 1), true);
+        grid.link_cell((0, 1), (1, 1), true);
+        let segment = |start, end| WallSegment { start, end };
+        assert_eq!(
+            grid.wall_segments(),
+            vec![
+                segment((0, 0), (2, 0)),
+                segment((0, 1), (1, 1)),
+                segment((0, 2), (2, 2)),
+                segment((0, 0), (0, 2)),
+                segment((2, 0), (2, 2)),
+            ]
+        );
+        assert!(segment((0, 1), (1, 1)).is_horizontal());
+        assert_eq!(
+            segment((0, 1), (1, 1)).rect(0.5),
+            ((-0.25, 0.75), (1.25, 1.25))
+        );
+        assert_eq!(
+            segment((2, 0), (2, 2)).rect(0.5),
+            ((1.75, -0.25), (2.25, 2.25))
+        );
+    }
+
+    #[test]
+    fn test_tile_mask() {
+        let grid = "\
++---+---+---+
+|           |
++   +   +---+
+|   |       |
++---+---+---+
+"
+        .parse::<Grid>()
+        .unwrap();
+
+        assert_eq!(
+            grid.tile_mask((0, 0)),
+            Some(WALL_NORTH | WALL_WEST | CORNER_SOUTH_EAST)
+        );
+        // Open to the south and to both sides, with the ends of two walls at its lower corners.
+        assert_eq!(
+            grid.tile_mask((1, 0)),
+            Some(WALL_NORTH | CORNER_SOUTH_WEST | CORNER_SOUTH_EAST)
+        );
+        assert_eq!(
+            grid.tile_mask((2, 0)),
+            Some(WALL_NORTH | WALL_EAST | WALL_SOUTH)
+        );
+        assert_eq!(
+            grid.tile_mask((1, 1)),
+            Some(WALL_SOUTH | WALL_WEST | CORNER_NORTH_EAST)
+        );
+        assert_eq!(
+            grid.tile_mask((0, 1)),
+            Some(WALL_EAST | WALL_SOUTH | WALL_WEST)
+        );
+        assert_eq!(grid.tile_mask((3, 0)), None);
+    }
+}
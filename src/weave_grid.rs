@@ -0,0 +1,428 @@
+use crate::grid::{
+    write_ascii, Direction, Grid, GridError, GridIter, GridRowsIter, Position, WALL_EAST,
+    WALL_NORTH, WALL_SOUTH, WALL_WEST,
+};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The axis a passage runs along.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Passage {
+    Horizontal,
+    Vertical,
+}
+
+/// A grid whose passages may tunnel under a perpendicular corridor.
+///
+/// The visible ("over") cells and their links live in a plain `Grid`. A tunnel from `a` to `c`
+/// passes beneath the over-cell `b` between them and is recorded as a hidden under-cell at `b`,
+/// which is always open at both ends along its `Passage` axis.
+#[derive(PartialEq, Debug)]
+pub struct WeaveGrid {
+    grid: Grid,
+    under_cells: HashMap<Position, Passage>,
+}
+
+impl WeaveGrid {
+    pub fn new(rows: usize, columns: usize) -> Self {
+        WeaveGrid {
+            grid: Grid::new(rows, columns),
+            under_cells: HashMap::new(),
+        }
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn rows(&self) -> usize {
+        self.grid.rows()
+    }
+
+    pub fn columns(&self) -> usize {
+        self.grid.columns()
+    }
+
+    pub fn regenerate(&mut self) {
+        self.grid.regenerate();
+        self.under_cells.clear();
+    }
+
+    pub fn iter(&self) -> GridIter {
+        self.grid.iter()
+    }
+
+    pub fn iter_rows(&self) -> GridRowsIter {
+        self.grid.iter_rows()
+    }
+
+    pub fn north_of_cell(&self, pos: Position) -> Option<Position> {
+        self.grid.north_of_cell(pos)
+    }
+
+    pub fn south_of_cell(&self, pos: Position) -> Option<Position> {
+        self.grid.south_of_cell(pos)
+    }
+
+    pub fn east_of_cell(&self, pos: Position) -> Option<Position> {
+        self.grid.east_of_cell(pos)
+    }
+
+    pub fn west_of_cell(&self, pos: Position) -> Option<Position> {
+        self.grid.west_of_cell(pos)
+    }
+
+    /// The axis of the passage tunnelling under `pos`, if there is one.
+    pub fn under_cell(&self, pos: Position) -> Option<Passage> {
+        self.under_cells.get(&pos).copied()
+    }
+
+    pub fn under_cells(&self) -> impl Iterator<Item = (Position, Passage)> + '_ {
+        self.under_cells
+            .iter()
+            .map(|(pos, passage)| (*pos, *passage))
+    }
+
+    /// Whether the over-cell at `pos` leads towards `direction`, either linked to its neighbor
+    /// or at the end of a tunnel under it.
+    fn leads(&self, pos: Position, direction: Direction) -> bool {
+        let passage = match direction {
+            Direction::North | Direction::South => Passage::Vertical,
+            Direction::East | Direction::West => Passage::Horizontal,
+        };
+        match self.grid.neighbor_of_cell(pos, direction) {
+            Some(next) => self.grid.is_linked(pos, next) || self.under_cell(next) == Some(passage),
+            None => false,
+        }
+    }
+
+    /// Whether the over-cell at `pos` is a straight east-west corridor.
+    pub fn is_horizontal_passage(&self, pos: Position) -> bool {
+        self.leads(pos, Direction::East)
+            && self.leads(pos, Direction::West)
+            && !self.leads(pos, Direction::North)
+            && !self.leads(pos, Direction::South)
+    }
+
+    /// Whether the over-cell at `pos` is a straight north-south corridor.
+    pub fn is_vertical_passage(&self, pos: Position) -> bool {
+        self.leads(pos, Direction::North)
+            && self.leads(pos, Direction::South)
+            && !self.leads(pos, Direction::East)
+            && !self.leads(pos, Direction::West)
+    }
+
+    pub fn can_tunnel_north(&self, pos: Position) -> Option<Position> {
+        let north = self.north_of_cell(pos)?;
+        if self.is_horizontal_passage(north) && self.under_cell(north).is_none() {
+            self.north_of_cell(north)
+        } else {
+            None
+        }
+    }
+
+    pub fn can_tunnel_south(&self, pos: Position) -> Option<Position> {
+        let south = self.south_of_cell(pos)?;
+        if self.is_horizontal_passage(south) && self.under_cell(south).is_none() {
+            self.south_of_cell(south)
+        } else {
+            None
+        }
+    }
+
+    pub fn can_tunnel_east(&self, pos: Position) -> Option<Position> {
+        let east = self.east_of_cell(pos)?;
+        if self.is_vertical_passage(east) && self.under_cell(east).is_none() {
+            self.east_of_cell(east)
+        } else {
+            None
+        }
+    }
+
+    pub fn can_tunnel_west(&self, pos: Position) -> Option<Position> {
+        let west = self.west_of_cell(pos)?;
+        if self.is_vertical_passage(west) && self.under_cell(west).is_none() {
+            self.west_of_cell(west)
+        } else {
+            None
+        }
+    }
+
+    /// Adjacent cells plus the cells reachable by tunnelling under a neighbor.
+    pub fn neighbors_of_cell(&self, pos: Position) -> Vec<Position> {
+        let mut neighbors = vec![];
+        let directions = [
+            (self.north_of_cell(pos), self.can_tunnel_north(pos)),
+            (self.south_of_cell(pos), self.can_tunnel_south(pos)),
+            (self.east_of_cell(pos), self.can_tunnel_east(pos)),
+            (self.west_of_cell(pos), self.can_tunnel_west(pos)),
+        ];
+        for (adjacent, tunnel) in directions.iter() {
+            neighbors.extend(adjacent);
+            neighbors.extend(tunnel);
+        }
+        neighbors
+    }
+
+    /// Links two over-cells. Cells two apart along a row or column are joined by a tunnel
+    /// under the cell between them.
+    ///
+    /// Panics if either cell is outside the grid, the cells are neither adjacent nor two apart,
+    /// or the cell between them can't take a tunnel; `try_link` reports those as errors
+    /// instead.
+    pub fn link_cell(&mut self, from: Position, to: Position) {
+        self.try_link(from, to).unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    pub fn try_link(&mut self, from: Position, to: Position) -> Result<(), GridError> {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        if dx.abs() + dy.abs() == 1 {
+            return self.grid.try_link(from, to, true);
+        }
+        // With both ends inside the grid, so is the cell between them.
+        for pos in [from, to].iter() {
+            if self.grid.cell(*pos).is_none() {
+                return Err(GridError::OutOfBounds(*pos));
+            }
+        }
+        if !((dx == 0 && dy.abs() == 2) || (dy == 0 && dx.abs() == 2)) {
+            return Err(GridError::NotAdjacent(from, to));
+        }
+        let under = (from.0 + dx / 2, from.1 + dy / 2);
+        let (passage, crosses) = if dy == 0 {
+            (Passage::Horizontal, self.is_vertical_passage(under))
+        } else {
+            (Passage::Vertical, self.is_horizontal_passage(under))
+        };
+        if !crosses || self.under_cell(under).is_some() {
+            return Err(GridError::CannotTunnel(under));
+        }
+        self.under_cells.insert(under, passage);
+        Ok(())
+    }
+
+    /// Whether a passage, over or under, crosses the wall east of `pos`.
+    pub fn is_open_east(&self, pos: Position) -> bool {
+        match self.east_of_cell(pos) {
+            Some(east) => {
+                self.grid.is_linked(pos, east)
+                    || self.under_cell(pos) == Some(Passage::Horizontal)
+                    || self.under_cell(east) == Some(Passage::Horizontal)
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a passage, over or under, crosses the wall south of `pos`.
+    pub fn is_open_south(&self, pos: Position) -> bool {
+        match self.south_of_cell(pos) {
+            Some(south) => {
+                self.grid.is_linked(pos, south)
+                    || self.under_cell(pos) == Some(Passage::Vertical)
+                    || self.under_cell(south) == Some(Passage::Vertical)
+            }
+            None => false,
+        }
+    }
+
+    /// Like `Grid::walls_of_cell`, but walls crossed by a tunnel are left open.
+    pub fn walls_of_cell(&self, pos: Position) -> Option<u8> {
+        let mut walls = self.grid.walls_of_cell(pos)? & (WALL_NORTH | WALL_WEST);
+        if !self.is_open_east(pos) {
+            walls |= WALL_EAST;
+        }
+        if !self.is_open_south(pos) {
+            walls |= WALL_SOUTH;
+        }
+        Some(walls)
+    }
+
+    /// Over-cells reachable from `pos` in one step, following tunnels to their far end.
+    pub fn links_of_cell(&self, pos: Position) -> Vec<Position> {
+        let mut links = vec![];
+        let directions = [
+            (self.north_of_cell(pos), Passage::Vertical, (0, -1)),
+            (self.south_of_cell(pos), Passage::Vertical, (0, 1)),
+            (self.east_of_cell(pos), Passage::Horizontal, (1, 0)),
+            (self.west_of_cell(pos), Passage::Horizontal, (-1, 0)),
+        ];
+        for (adjacent, passage, (dx, dy)) in directions.iter() {
+            if let Some(adjacent) = *adjacent {
+                if self.grid.is_linked(pos, adjacent) {
+                    links.push(adjacent);
+                } else if self.under_cell(adjacent) == Some(*passage) {
+                    links.push((adjacent.0 + dx, adjacent.1 + dy));
+                }
+            }
+        }
+        links
+    }
+}
+
+/// Over-cells with a tunnel beneath them are drawn as a bridge: `| |` over a horizontal
+/// tunnel and `===` over a vertical one.
+impl fmt::Display for WeaveGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_ascii(
+            f,
+            self.rows(),
+            self.columns(),
+            |pos| self.walls_of_cell(pos).unwrap_or(0) & WALL_EAST == 0,
+            |pos| self.walls_of_cell(pos).unwrap_or(0) & WALL_SOUTH == 0,
+            |pos| match self.under_cell(pos) {
+                Some(Passage::Horizontal) => "| |",
+                Some(Passage::Vertical) => "===",
+                None => "   ",
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary_tree::binary_tree_weave;
+    use crate::grid::GridError;
+    use crate::recursive_backtracker::recursive_backtracker_weave;
+    use crate::sidewinder::sidewinder_weave;
+    use crate::test_utils::{check, check_debug};
+    use crate::weave_grid::{Passage, WeaveGrid};
+    use expect_test::expect;
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    fn crossing() -> WeaveGrid {
+        let mut grid = WeaveGrid::new(3, 3);
+        grid.link_cell((0, 1), (1, 1));
+        grid.link_cell((1, 1), (2, 1));
+        grid.link_cell((1, 0), (1, 2));
+        grid
+    }
+
+    #[test]
+    fn test_tunnel_neighbors() {
+        let mut grid = WeaveGrid::new(3, 3);
+        grid.link_cell((0, 1), (1, 1));
+        grid.link_cell((1, 1), (2, 1));
+        check_debug(
+            grid.neighbors_of_cell((1, 0)),
+            expect![[r#"
+                [
+                    (
+                        1,
+                        1,
+                    ),
+                    (
+                        1,
+                        2,
+                    ),
+                    (
+                        2,
+                        0,
+                    ),
+                    (
+                        0,
+                        0,
+                    ),
+                ]
+            "#]],
+        );
+        assert_eq!(grid.can_tunnel_east((0, 0)), None);
+    }
+
+    #[test]
+    fn test_display_weave_grid() {
+        let grid = crossing();
+        check(
+            &grid,
+            expect![[r#"
+                +---+---+---+
+                |   |   |   |
+                +---+   +---+
+                |    ===    |
+                +---+   +---+
+                |   |   |   |
+                +---+---+---+
+            "#]],
+        );
+        assert_eq!(grid.links_of_cell((1, 0)), vec![(1, 2)]);
+        assert_eq!(grid.links_of_cell((1, 1)), vec![(2, 1), (0, 1)]);
+    }
+
+    #[test]
+    fn test_try_link() {
+        let mut grid = WeaveGrid::new(3, 3);
+        assert_eq!(
+            grid.try_link((2, 1), (4, 1)),
+            Err(GridError::OutOfBounds((4, 1)))
+        );
+        assert_eq!(
+            grid.try_link((1, -1), (1, 1)),
+            Err(GridError::OutOfBounds((1, -1)))
+        );
+        assert_eq!(
+            grid.try_link((0, 0), (2, 2)),
+            Err(GridError::NotAdjacent((0, 0), (2, 2)))
+        );
+        assert_eq!(
+            grid.try_link((3, 0), (2, 0)),
+            Err(GridError::OutOfBounds((3, 0)))
+        );
+        assert_eq!(
+            grid.try_link((0, 0), (0, 2)),
+            Err(GridError::CannotTunnel((0, 1)))
+        );
+        assert_eq!(grid, WeaveGrid::new(3, 3));
+
+        let mut grid = crossing();
+        assert_eq!(
+            grid.try_link((0, 1), (2, 1)),
+            Err(GridError::CannotTunnel((1, 1)))
+        );
+        grid.link_cell((0, 0), (1, 0));
+        assert_eq!(
+            grid.try_link((1, 0), (1, 2)),
+            Err(GridError::CannotTunnel((1, 1)))
+        );
+
+        let mut grid = WeaveGrid::new(3, 3);
+        grid.link_cell((1, 0), (1, 1));
+        grid.link_cell((1, 1), (1, 2));
+        assert_eq!(grid.try_link((0, 1), (2, 1)), Ok(()));
+        assert_eq!(grid.under_cell((1, 1)), Some(Passage::Horizontal));
+    }
+
+    #[test]
+    fn test_tunnels_pass_under_straight_passages() {
+        type Generate = fn(&mut WeaveGrid, &mut StdRng) -> Result<(), GridError>;
+        let generators: [Generate; 3] = [
+            binary_tree_weave,
+            sidewinder_weave,
+            recursive_backtracker_weave,
+        ];
+        for generate in generators.iter() {
+            for seed in 0..300 {
+                let mut grid = WeaveGrid::new(8, 8);
+                generate(&mut grid, &mut StdRng::seed_from_u64(seed)).unwrap();
+                // The over-cell leads straight across the tunnel, and nowhere else.
+                for (pos, passage) in grid.under_cells() {
+                    let mut across = match passage {
+                        Passage::Vertical => vec![(-1, 0), (1, 0)],
+                        Passage::Horizontal => vec![(0, -1), (0, 1)],
+                    };
+                    across.sort();
+                    let mut leads = grid
+                        .links_of_cell(pos)
+                        .iter()
+                        .map(|(x, y)| ((x - pos.0).signum(), (y - pos.1).signum()))
+                        .collect::<Vec<_>>();
+                    leads.sort();
+                    assert_eq!(
+                        leads, across,
+                        "seed {}: {:?} under {:?}",
+                        seed, passage, pos
+                    );
+                }
+            }
+        }
+    }
+}